@@ -1,56 +1,51 @@
-use window::{Window, ShaderBuilder, Shader, Shape, ShapeBuilder, Transform, Group, Graphic};
-use std::mem::MaybeUninit;
-
-static mut CONTEXT: MaybeUninit<Context> = MaybeUninit::uninit();
+use window::{Group, RasterId, Shader, Shape, ShapeBuilder, Transform, Window};
 
 pub struct Context {
-    window: Window,
     shader: Shader,
     group: Group,
-    graphic: Graphic,
+    graphic: RasterId,
+}
+
+impl Context {
+    fn new(window: &mut Window) -> Self {
+        let mut shader: Shader = window.shader_new(include!(concat!(env!("OUT_DIR"), "/res/", "graphic", ".rs")));
+        let shape: Shape = ShapeBuilder::new(&mut shader)
+            .vert(&[
+                  -1.0, 1.0, 0.0,  0.0, 1.0,
+                  1.0, 1.0, 0.0,  1.0, 1.0,
+                  1.0, -1.0, 0.0,  1.0, 0.0,
+                  -1.0, -1.0, 0.0,  0.0, 0.0,
+                  -1.0, 1.0, 0.0,  0.0, 1.0,
+                  1.0, -1.0, 0.0,  1.0, 0.0,
+            ])
+            .face(Transform::new().scale(0.25, 0.25, 0.25))
+            .finish();
+        let mut group: Group = window.group_new();
+        group.write((0, 0), &shape, &Transform::new().translate(0.5, 0.5 * window.aspect(), 1.0));
+
+        let texture: &[u8] = include_bytes!("../../../res/box.png");
+        let data = std::io::Cursor::new(texture);
+        let decoder = png_pong::FrameDecoder::<_, pix::rgb::SRgba8>::new(data);
+        let png_pong::Frame { raster, delay: _ } = decoder
+            .last()
+            .expect("No frames in PNG")
+            .expect("PNG parsing error");
+
+        let graphic = window.graphic(raster.as_u8_slice(), raster.width() as usize, raster.height() as usize);
+
+        window.background(0.1, 0.0, 0.1);
+
+        Context { shader, group, graphic }
+    }
 }
 
 fn main() {
-    pub fn run(nanos: u64) {
-        let context: &mut Context = unsafe { &mut *CONTEXT.as_mut_ptr() };
+    let mut context = None;
+    let mut window = Window::new("My Window", move |window, _elapsed| {
+        let context = context.get_or_insert_with(|| Context::new(window));
 
-        context.window.draw_graphic(&context.shader, &mut context.group, &context.graphic);
-    }
+        window.draw_graphic(&context.shader, &context.group, &context.graphic);
+    });
 
-    println!("{:?}", std::thread::current().name());
-    let mut window = window::Window::new("My Window", run);
-    let mut shader: Shader = window.shader_new(include!(concat!(env!("OUT_DIR"), "/res/", "graphic", ".rs")));
-    let shape: Shape = ShapeBuilder::new(&mut shader)
-        .vert(&[
-              -1.0, 1.0, 0.0,  0.0, 1.0,
-              1.0, 1.0, 0.0,  1.0, 1.0,
-              1.0, -1.0, 0.0,  1.0, 0.0,
-              -1.0, -1.0, 0.0,  0.0, 0.0,
-              -1.0, 1.0, 0.0,  0.0, 1.0,
-              1.0, -1.0, 0.0,  1.0, 0.0,
-        ])
-        .face(Transform::new().scale(0.25, 0.25, 0.25))
-        .finish();
-    let mut group: Group = window.group_new();
-    group.push(&shape, &Transform::new().translate(0.5, 0.5 * window.aspect(), 1.0));
-
-    let texture: &[u8] = include_bytes!("../../../res/box.png");
-    let data = std::io::Cursor::new(texture);
-    let decoder = png_pong::FrameDecoder::<_, pix::rgb::SRgba8>::new(data);
-    let png_pong::Frame { raster, delay: _ } = decoder
-        .last()
-        .expect("No frames in PNG")
-        .expect("PNG parsing error");
-
-    let graphic = window.graphic(raster.as_u8_slice(), raster.width() as usize, raster.height() as usize);
-
-    let mut context = Context {
-        window, shader, group, graphic,
-    };
-
-    context.window.background(0.1, 0.0, 0.1);
-
-    unsafe { CONTEXT = MaybeUninit::new(context) };
-
-    while unsafe { (*CONTEXT.as_mut_ptr()).window.run() } {  }
+    while window.run() {}
 }
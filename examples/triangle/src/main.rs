@@ -1,42 +1,37 @@
-use window::{Window, ShaderBuilder, Shader, Shape, ShapeBuilder, Transform, Group};
-use std::mem::MaybeUninit;
-
-static mut CONTEXT: MaybeUninit<Context> = MaybeUninit::uninit();
+use window::{Group, Shader, Shape, ShapeBuilder, Transform, Window};
 
 pub struct Context {
-    window: Window,
     shader: Shader,
     group: Group,
 }
 
-fn main() {
-    pub fn run(nanos: u64) {
-        let context: &mut Context = unsafe { &mut *CONTEXT.as_mut_ptr() };
-
-        context.window.draw(&context.shader, &mut context.group);
+impl Context {
+    fn new(window: &mut Window) -> Self {
+        let mut shader: Shader = window.shader_new(include!(concat!(env!("OUT_DIR"), "/res/", "color", ".rs")));
+        let shape: Shape = ShapeBuilder::new(&mut shader)
+            .vert(&[
+                  0.125, 0.895,   1.0, 0.0, 0.0,
+                  0.895, 0.895,   0.0, 1.0, 0.0,
+                  0.5,   0.125,   0.0, 0.0, 1.0,
+            ])
+            .face(Transform::new())
+            .finish();
+        let mut group: Group = window.group_new();
+        group.write((0, 0), &shape, &Transform::new());
+
+        window.background(0.1, 0.0, 0.1);
+
+        Context { shader, group }
     }
+}
 
-    println!("{:?}", std::thread::current().name());
-    let mut window = window::Window::new("My Window", run);
-    let mut shader: Shader = window.shader_new(include!(concat!(env!("OUT_DIR"), "/res/", "color", ".rs")));
-    let shape: Shape = ShapeBuilder::new(&mut shader)
-        .vert(&[
-              0.125, 0.895,   1.0, 0.0, 0.0,
-              0.895, 0.895,   0.0, 1.0, 0.0,
-              0.5,   0.125,   0.0, 0.0, 1.0,
-        ])
-        .face(Transform::new())
-        .finish();
-    let mut group: Group = window.group_new();
-    group.push(&shape, &Transform::new());
-
-    let mut context = Context {
-        window, shader, group
-    };
-
-    context.window.background(0.1, 0.0, 0.1);
+fn main() {
+    let mut context = None;
+    let mut window = Window::new("My Window", move |window, _elapsed| {
+        let context = context.get_or_insert_with(|| Context::new(window));
 
-    unsafe { CONTEXT = MaybeUninit::new(context) };
+        window.draw(&context.shader, &context.group);
+    });
 
-    while unsafe { (*CONTEXT.as_mut_ptr()).window.run() } {  }
+    while window.run() {}
 }
@@ -1,11 +1,10 @@
-pub struct Context {
-}
-
 fn main() {
-    pub fn run(nanos: u64) {
-    }
-
-    println!("{:?}", std::thread::current().name());
-    let mut window = window::Window::new("My Window", run);
+    let mut frames = 0u64;
+    let mut window = window::Window::new("My Window", move |_window, _elapsed| {
+        frames += 1;
+        if frames % 60 == 0 {
+            println!("{} frames", frames);
+        }
+    });
     while window.run() {}
 }
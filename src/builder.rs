@@ -0,0 +1,132 @@
+// Window
+// Copyright © 2019-2021 Jeron Aldaron Lau.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+use std::time::Duration;
+
+use crate::{Decorations, FrameStyle, Window, WindowError};
+
+/// The state a window starts in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WindowState {
+    /// A normal window with the size chosen by `WindowBuilder::size()`.
+    Windowed,
+    /// A window that fills the screen, leaving room for panels.
+    Maximized,
+    /// A window that covers the whole screen.
+    Fullscreen,
+}
+
+/// A builder for a `Window`.
+#[derive(Clone, Debug)]
+pub struct WindowBuilder {
+    pub(crate) title: String,
+    pub(crate) app_id: Option<String>,
+    pub(crate) size: (u16, u16),
+    pub(crate) min_size: Option<(u16, u16)>,
+    pub(crate) max_size: Option<(u16, u16)>,
+    pub(crate) state: WindowState,
+    pub(crate) close_on_request: bool,
+    pub(crate) fullscreen_key: bool,
+    pub(crate) decorations: Decorations,
+    pub(crate) frame: Option<FrameStyle>,
+}
+
+impl WindowBuilder {
+    /// Create a new `WindowBuilder` for a window with a title.  If the title
+    /// contains a NUL byte, `build()` returns `WindowError::InvalidString`.
+    ///
+    /// By default the window is 640x360 and starts maximized, and the app id
+    /// is the same as the title.
+    pub fn new(title: &str) -> Self {
+        WindowBuilder {
+            title: title.to_string(),
+            app_id: None,
+            size: (640, 360),
+            min_size: None,
+            max_size: None,
+            state: WindowState::Maximized,
+            close_on_request: true,
+            fullscreen_key: false,
+            decorations: Decorations::Server,
+            frame: None,
+        }
+    }
+
+    /// Set the app id, used to match the window to its `.desktop` file.  If
+    /// it contains a NUL byte, `build()` returns `WindowError::InvalidString`.
+    pub fn app_id(mut self, app_id: &str) -> Self {
+        self.app_id = Some(app_id.to_string());
+        self
+    }
+
+    /// Set the initial size of the window (in logical pixels).
+    pub fn size(mut self, width: u16, height: u16) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    /// Set the minimum size the window may be resized to.
+    pub fn min_size(mut self, width: u16, height: u16) -> Self {
+        self.min_size = Some((width, height));
+        self
+    }
+
+    /// Set the maximum size the window may be resized to.  Setting this to
+    /// the same as the minimum size makes a fixed-size window.
+    pub fn max_size(mut self, width: u16, height: u16) -> Self {
+        self.max_size = Some((width, height));
+        self
+    }
+
+    /// Set the state the window starts in.
+    pub fn state(mut self, state: WindowState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Set whether the window closes by itself when the user asks it to
+    /// (default: true).  If false, the app should handle
+    /// `WindowEvent::CloseRequested` and call `Window::close()` itself.
+    pub fn close_on_request(mut self, close: bool) -> Self {
+        self.close_on_request = close;
+        self
+    }
+
+    /// Set whether F11 toggles fullscreen (default: false).  When enabled, F11
+    /// presses and releases aren't reported as input.
+    pub fn fullscreen_key(mut self, enabled: bool) -> Self {
+        self.fullscreen_key = enabled;
+        self
+    }
+
+    /// Set who should draw the window decorations (default: the window
+    /// manager).  The window manager has the final say, which is reported by
+    /// `Window::decorations()`.
+    pub fn decorations(mut self, decorations: Decorations) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    /// Draw a built-in frame (title bar, buttons & resize borders) with this
+    /// style whenever the window manager leaves the decorations to the
+    /// client (default: no frame).
+    pub fn frame(mut self, style: FrameStyle) -> Self {
+        self.frame = Some(style);
+        self
+    }
+
+    /// Create the window, calling `run` once per frame.
+    pub fn build<F>(self, run: F) -> Result<Window, WindowError>
+    where
+        F: FnMut(&mut Window, Duration) + 'static,
+    {
+        Window::with_builder(&self, Box::new(run))
+    }
+}
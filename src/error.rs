@@ -0,0 +1,53 @@
+// Window
+// Copyright © 2019-2021 Jeron Aldaron Lau.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+use std::fmt::{Display, Formatter, Result};
+
+/// An error that occured while creating a window.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WindowError {
+    /// A shared library needed by the window manager backend isn't installed
+    /// (or is missing a symbol).
+    MissingLibrary(&'static str),
+    /// Couldn't connect to the window manager (compositor).
+    NoConnection,
+    /// The window manager doesn't provide a required global object.
+    MissingGlobal(&'static str),
+    /// Couldn't initialize the graphics library (EGL).
+    GraphicsInit,
+    /// The graphics library has no suitable framebuffer configuration.
+    GraphicsConfig,
+    /// Couldn't create the graphics context or its surface.
+    GraphicsContext,
+    /// A window title, app id or surrounding text contains a NUL byte, or
+    /// surrounding text is too long or has an offset that isn't on a
+    /// character boundary.
+    InvalidString,
+}
+
+impl Display for WindowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        use WindowError::*;
+        match self {
+            MissingLibrary(name) => write!(f, "Couldn't load {}", name),
+            NoConnection => write!(f, "Couldn't connect to window manager"),
+            MissingGlobal(name) => {
+                write!(f, "Window manager doesn't support {}", name)
+            }
+            GraphicsInit => write!(f, "Couldn't initialize EGL"),
+            GraphicsConfig => write!(f, "No matching EGL configuration"),
+            GraphicsContext => write!(f, "Couldn't create EGL context"),
+            InvalidString => write!(f, "String is invalid for window manager"),
+        }
+    }
+}
+
+impl std::error::Error for WindowError {}
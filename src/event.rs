@@ -0,0 +1,255 @@
+// Window
+// Copyright © 2019-2021 Jeron Aldaron Lau.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+/// An event sent to a window by the window manager.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum WindowEvent {
+    /// The user asked to close the window (for example, with the close
+    /// button).  Unless `WindowBuilder::close_on_request(false)` was set, the
+    /// window closes and `Window::run()` returns false.
+    CloseRequested,
+    /// The state of the window changed.
+    StateChanged(WindowStatus),
+    /// The window manager changed who draws the window decorations.
+    DecorationsChanged(Decorations),
+    /// The window got keyboard focus.  Keys that were already held down are
+    /// sent as `Input::Key` presses.
+    FocusGained,
+    /// The window lost keyboard focus.  Keys that were held down are sent as
+    /// `Input::Key` releases first.
+    FocusLost,
+    /// The pointer moved over the window.
+    PointerEntered,
+    /// The pointer left the window (also sent as `Input::PointerLeave`).
+    PointerLeft,
+    /// The pointer moved, or entered the window (sent once for each group of
+    /// pointer changes, with the latest position, which is also sent as
+    /// `Input::PointerX` and `Input::PointerY`).
+    PointerMoved(PointerMotion),
+    /// The pointer lock or confinement from `Window::lock_pointer()` or
+    /// `Window::confine_pointer()` became active (`true`) or inactive, for
+    /// example when the window gets or loses focus.
+    PointerLockChanged(bool),
+    /// The pointer moved, including when it's locked or at the edge of the
+    /// screen (sent once for each group of pointer changes).
+    RelativeMotion(RelativeMotion),
+    /// The window was scrolled (sent once for each group of pointer changes,
+    /// and also as `Input::ScrollX` and `Input::ScrollY`).
+    Scroll(Scroll),
+    /// Touch points that changed together (in one frame).  The first finger
+    /// down is also sent as `Input::Touch`, `Input::PointerX` and
+    /// `Input::PointerY`.
+    Touch(Vec<Touch>),
+    /// The window manager took over all current touch points (for example,
+    /// for a gesture): they should be treated as if they never happened.
+    TouchCancel,
+    /// A touchpad gesture began, changed or ended.  Pinches are also sent as
+    /// `Input::Pinch` (begin & end), `Input::PinchW` and `Input::PinchH` (the
+    /// scale relative to the previous update) and `Input::PinchZ` (rotation).
+    Gesture(Gesture),
+    /// The input method changed the text being composed (only sent while
+    /// text input is enabled with `Window::set_text_input()`).  It should be
+    /// drawn at the cursor, replacing the previous preedit text, until it's
+    /// committed (as `Input::Text`) or cleared (empty `text`).
+    Preedit {
+        /// Text being composed.
+        text: String,
+        /// Byte range of `text` to draw as the cursor or selection, or `None`
+        /// to hide the cursor.
+        cursor: Option<(usize, usize)>,
+    },
+    /// The input method wants to delete text around the cursor, before the
+    /// next committed text is inserted.
+    DeleteSurroundingText {
+        /// Bytes to delete before the cursor.
+        before: usize,
+        /// Bytes to delete after the cursor.
+        after: usize,
+    },
+}
+
+/// Who draws the window decorations (title bar and borders).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Decorations {
+    /// The app draws them (or there are none).
+    Client,
+    /// The window manager draws them.
+    Server,
+}
+
+/// The state of a window, as reported by the window manager.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WindowStatus {
+    /// The window is maximized.
+    pub maximized: bool,
+    /// The window is fullscreen.
+    pub fullscreen: bool,
+    /// The window is being resized by the user.
+    pub resizing: bool,
+    /// The window is active (has focus), and should be drawn as such.
+    pub activated: bool,
+    /// The left edge of the window is against another window or the screen
+    /// edge (only reported by some window managers).
+    pub tiled_left: bool,
+    /// The right edge of the window is tiled.
+    pub tiled_right: bool,
+    /// The top edge of the window is tiled.
+    pub tiled_top: bool,
+    /// The bottom edge of the window is tiled.
+    pub tiled_bottom: bool,
+}
+
+/// A new pointer position.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PointerMotion {
+    /// X position, in the same units as `Input::PointerX`.
+    pub x: f32,
+    /// Y position, in the same units as `Input::PointerY`.
+    pub y: f32,
+    /// X position, in logical pixels from the left of the window.
+    pub logical_x: f32,
+    /// Y position, in logical pixels from the top of the window.
+    pub logical_y: f32,
+    /// When the pointer moved, in milliseconds (from an unspecified start),
+    /// or 0 for the position the pointer entered the window at.
+    pub time: u32,
+}
+
+/// How far the pointer moved, in logical pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RelativeMotion {
+    /// Horizontal movement, with pointer acceleration.
+    pub dx: f32,
+    /// Vertical movement, with pointer acceleration.
+    pub dy: f32,
+    /// Horizontal movement without pointer acceleration (the same as `dx`
+    /// if the device has none), for things like camera control.
+    pub dx_unaccel: f32,
+    /// Vertical movement without pointer acceleration.
+    pub dy_unaccel: f32,
+    /// When the pointer moved, in microseconds (from an unspecified start).
+    pub time: u64,
+}
+
+/// Scrolling from a mouse wheel, touchpad or other device.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Scroll {
+    /// What kind of device is scrolling.
+    pub source: ScrollSource,
+    /// Horizontal distance (positive is right), in logical pixels.
+    pub x: f32,
+    /// Vertical distance (positive is down), in logical pixels.
+    pub y: f32,
+    /// Horizontal wheel notches, which are fractional for high-resolution
+    /// wheels (0 for sources without notches).
+    pub steps_x: f32,
+    /// Vertical wheel notches.
+    pub steps_y: f32,
+    /// Horizontal scrolling stopped (for example, the fingers were lifted
+    /// off the touchpad), so kinetic scrolling may start or stop.
+    pub stop_x: bool,
+    /// Vertical scrolling stopped.
+    pub stop_y: bool,
+    /// When the scrolling happened, in milliseconds (from an unspecified
+    /// start).
+    pub time: u32,
+}
+
+/// What kind of device is scrolling.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ScrollSource {
+    /// The window manager didn't say.
+    Unknown,
+    /// A mouse wheel, which scrolls in notches.
+    Wheel,
+    /// Fingers on a touchpad or touchscreen, which send a stop at the end.
+    Finger,
+    /// A continuous device without notches, like a trackpoint.
+    Continuous,
+    /// Tilting a mouse wheel sideways, which scrolls in notches.
+    WheelTilt,
+}
+
+/// A touchpad gesture.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Gesture {
+    /// What kind of gesture it is.
+    pub kind: GestureKind,
+    /// What happened to the gesture.
+    pub phase: GesturePhase,
+    /// How many fingers are on the touchpad.
+    pub fingers: u32,
+    /// Horizontal movement of the fingers since the last update, in logical
+    /// pixels (0 except for `GesturePhase::Update`).
+    pub dx: f32,
+    /// Vertical movement of the fingers since the last update.
+    pub dy: f32,
+    /// Distance between the fingers, relative to when the pinch began (1 for
+    /// other gestures).
+    pub scale: f32,
+    /// Rotation of the fingers since the last update, in degrees clockwise
+    /// (0 for other gestures).
+    pub rotation: f32,
+    /// When the gesture changed, in milliseconds (from an unspecified start).
+    pub time: u32,
+}
+
+/// A kind of touchpad gesture.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GestureKind {
+    /// Fingers moving together.
+    Swipe,
+    /// Fingers moving closer together, further apart, or rotating.
+    Pinch,
+    /// Fingers resting on the touchpad without moving (for example, to stop
+    /// kinetic scrolling).
+    Hold,
+}
+
+/// What happened to a touchpad gesture.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GesturePhase {
+    /// The gesture started.
+    Begin,
+    /// The fingers moved.
+    Update,
+    /// The gesture finished.
+    End,
+    /// The gesture was cancelled (for example, a finger was added), and
+    /// should be undone.
+    Cancel,
+}
+
+/// A change to a touch point on a touchscreen.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Touch {
+    /// Identifies the touch point from when it's down until it's up (ids of
+    /// touch points that are up may be reused).
+    pub id: i32,
+    /// What happened to the touch point.
+    pub phase: TouchPhase,
+    /// X position, in the same units as `Input::PointerX`.
+    pub x: f32,
+    /// Y position, in the same units as `Input::PointerY`.
+    pub y: f32,
+}
+
+/// What happened to a touch point.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TouchPhase {
+    /// The touch point started (finger down).
+    Down,
+    /// The touch point moved.
+    Move,
+    /// The touch point ended (finger up), at its last position.
+    Up,
+}
@@ -8,47 +8,198 @@
 // At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
 // LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
 
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::rc::{Rc, Weak};
 use std::task::{Context, Poll, Waker};
 
 use human::Input;
 
-// True for async thread, false for main thread.
-static PIPE_LOCK: AtomicBool = AtomicBool::new(true);
-// Pipe data.
-static mut PIPE: (Vec<Input>, Option<Waker>) = (vec![], None);
+use crate::WindowId;
+
+thread_local! {
+    // Input of the applications on this thread (which can't leave it), and
+    // the listeners waiting for it.
+    static LISTENERS: RefCell<Listeners> = const {
+        RefCell::new(Listeners {
+            apps: Vec::new(),
+            wakers: Vec::new(),
+            sequence: 0,
+        })
+    };
+}
+
+struct Listeners {
+    // Dropped with the application.
+    apps: Vec<Weak<RefCell<Queues>>>,
+    wakers: Vec<Waker>,
+    // Order of input across all windows.
+    sequence: u64,
+}
+
+// Input queues of the windows of one application, each kept until the window
+// is closed and all of its input is read.
+type Queues = Vec<Rc<RefCell<Queue>>>;
+
+#[derive(Default)]
+struct Queue {
+    inputs: VecDeque<(u64, WindowId, Input)>,
+    closed: bool,
+}
+
+/// Input sent to the windows of one application, waiting to be read by input
+/// listeners (on the same thread).  Unread input is dropped with it.
+pub(super) struct AppInput(Rc<RefCell<Queues>>);
+
+impl AppInput {
+    pub(super) fn new() -> Self {
+        let queues = Rc::new(RefCell::new(Vec::new()));
+        LISTENERS.with(|listeners| {
+            listeners.borrow_mut().apps.push(Rc::downgrade(&queues));
+        });
+        AppInput(queues)
+    }
+
+    /// Make a queue for a new window.
+    pub(super) fn queue(&self) -> InputQueue {
+        let queue = Rc::new(RefCell::new(Queue::default()));
+        self.0.borrow_mut().push(queue.clone());
+        InputQueue(queue)
+    }
+}
+
+/// Input sent to one window, waiting to be read by input listeners.
+pub(super) struct InputQueue(Rc<RefCell<Queue>>);
+
+impl InputQueue {
+    /// Add input to the end of the queue.  Listeners aren't woken until
+    /// `wake_listeners()` is called.
+    pub(super) fn push(&self, id: WindowId, input: Input) {
+        let sequence = LISTENERS.with(|listeners| {
+            let mut listeners = listeners.borrow_mut();
+            listeners.sequence += 1;
+            listeners.sequence
+        });
+        self.0.borrow_mut().inputs.push_back((sequence, id, input));
+    }
+}
+
+impl Drop for InputQueue {
+    fn drop(&mut self) {
+        // Input that's already queued can still be read.
+        self.0.borrow_mut().closed = true;
+    }
+}
+
+/// Wake listeners waiting for input.
+pub(super) fn wake_listeners() {
+    let wakers = LISTENERS
+        .with(|listeners| std::mem::take(&mut listeners.borrow_mut().wakers));
+    for waker in wakers {
+        waker.wake();
+    }
+}
 
 pub(super) struct InputListener;
 
 impl Future for InputListener {
-    type Output = Input;
+    type Output = (WindowId, Input);
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        if PIPE_LOCK.load(Ordering::SeqCst) {
-            if let Some(input) = unsafe { PIPE.0.pop() } {
-                Poll::Ready(input)
+        LISTENERS.with(|listeners| {
+            let mut listeners = listeners.borrow_mut();
+            listeners.apps.retain(|app| app.strong_count() != 0);
+
+            // Find the oldest input of all windows.
+            let queues: Vec<_> = listeners
+                .apps
+                .iter()
+                .filter_map(Weak::upgrade)
+                .flat_map(|app| app.borrow().clone())
+                .collect();
+            let oldest = queues
+                .iter()
+                .filter_map(|queue| {
+                    Some((queue.borrow().inputs.front()?.0, queue))
+                })
+                .min_by_key(|(sequence, _)| *sequence);
+            let input = oldest
+                .and_then(|(_, queue)| queue.borrow_mut().inputs.pop_front());
+            for app in listeners.apps.iter().filter_map(Weak::upgrade) {
+                app.borrow_mut().retain(|queue| {
+                    let queue = queue.borrow();
+                    !queue.closed || !queue.inputs.is_empty()
+                });
+            }
+
+            if let Some((_, id, input)) = input {
+                Poll::Ready((id, input))
             } else {
-                PIPE_LOCK.store(false, Ordering::SeqCst);
-                unsafe { PIPE.1 = Some(cx.waker().clone()) };
+                if !listeners.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                    listeners.wakers.push(cx.waker().clone());
+                }
                 Poll::Pending
             }
-        } else {
-            Poll::Pending
-        }
+        })
     }
 }
 
-// Do not call this function more than once without waiting for some hardware
-// event between calls.  Doing so will cause input to be lost.  Only call this
-// function from the main thread.
-pub(super) unsafe fn push_inputs(inputs: Vec<Input>) {
-    if !PIPE_LOCK.load(Ordering::SeqCst) {
-        if let Some(waker) = PIPE.1.take() {
-            PIPE.0.extend(inputs);
-            PIPE_LOCK.store(true, Ordering::SeqCst);
-            waker.wake();
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::ptr::null;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    use human::Input;
+
+    use super::{AppInput, InputListener};
+    use crate::WindowId;
+
+    // A waker that does nothing (so never uses its data pointer).
+    fn waker() -> Waker {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(
+            |_| RawWaker::new(null(), &VTABLE),
+            drop,
+            drop,
+            drop,
+        );
+
+        unsafe { Waker::from_raw(RawWaker::new(null(), &VTABLE)) }
+    }
+
+    fn poll() -> Option<(WindowId, char)> {
+        let waker = waker();
+        let mut cx = Context::from_waker(&waker);
+        match Pin::new(&mut InputListener).poll(&mut cx) {
+            Poll::Ready((id, Input::Text(c))) => Some((id, c)),
+            Poll::Ready(input) => panic!("unexpected input {:?}", input),
+            Poll::Pending => None,
         }
     }
+
+    #[test]
+    fn oldest_input_first_across_windows() {
+        let (a, b) = (WindowId::new(), WindowId::new());
+        let app = AppInput::new();
+        let queue_a = app.queue();
+        let queue_b = app.queue();
+
+        queue_a.push(a, Input::Text('1'));
+        queue_b.push(b, Input::Text('2'));
+        queue_b.push(b, Input::Text('3'));
+        queue_a.push(a, Input::Text('4'));
+        // Input queued before a window closes is still delivered.
+        drop(queue_b);
+        queue_a.push(a, Input::Text('5'));
+
+        assert_eq!(poll(), Some((a, '1')));
+        assert_eq!(poll(), Some((b, '2')));
+        assert_eq!(poll(), Some((b, '3')));
+        assert_eq!(poll(), Some((a, '4')));
+        assert_eq!(poll(), Some((a, '5')));
+        assert_eq!(poll(), None);
+    }
 }
@@ -0,0 +1,404 @@
+// Window
+// Copyright © 2019-2021 Jeron Aldaron Lau.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+//! Built-in client-side decorations (title bar & resize borders), for window
+//! managers that don't draw them.
+
+mod font;
+
+use crate::{
+    Draw, Group, RasterId, ResizeEdge, Shader, ShaderBuilder, ShapeBuilder,
+    Transform,
+};
+
+/// Colors for the built-in window frame, set with `WindowBuilder::frame()`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FrameStyle {
+    /// Title bar & border color when the window is active.
+    pub active: [f32; 3],
+    /// Title bar & border color when the window isn't active.
+    pub inactive: [f32; 3],
+    /// Title text & button icon color.
+    pub text: [f32; 3],
+    /// Background of the minimize & maximize buttons under the pointer.
+    pub hover: [f32; 3],
+    /// Background of the close button under the pointer.
+    pub close_hover: [f32; 3],
+}
+
+impl Default for FrameStyle {
+    fn default() -> Self {
+        FrameStyle {
+            active: [0.2, 0.2, 0.2],
+            inactive: [0.35, 0.35, 0.35],
+            text: [1.0, 1.0, 1.0],
+            hover: [0.45, 0.45, 0.45],
+            close_hover: [0.8, 0.2, 0.2],
+        }
+    }
+}
+
+/// Height of the title bar, in pixels.
+const TITLE_HEIGHT: u16 = 28;
+/// Width of the resize borders, in pixels.
+const BORDER: u16 = 4;
+/// Size of the corner resize areas along each edge, in pixels.
+const CORNER: i32 = 16;
+/// How many pixels wide & tall each font pixel is.
+const SCALE: usize = 2;
+
+/// Space taken by the frame: (left, top, right, bottom), in pixels.
+pub(crate) const MARGINS: (u16, u16, u16, u16) =
+    (BORDER, TITLE_HEIGHT, BORDER, BORDER);
+
+/// A button on the title bar.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum FrameButton {
+    Minimize,
+    Maximize,
+    Close,
+}
+
+/// What a point on the surface is part of.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum FrameHit {
+    Content,
+    TitleBar,
+    Button(FrameButton),
+    Edge(ResizeEdge),
+}
+
+// Buttons, from right to left.
+const BUTTONS: [FrameButton; 3] = [
+    FrameButton::Close,
+    FrameButton::Maximize,
+    FrameButton::Minimize,
+];
+
+/// Find what a point (in pixels) on a framed surface of `size` is part of.
+pub(crate) fn hit(size: (u16, u16), x: i32, y: i32) -> FrameHit {
+    use ResizeEdge::*;
+
+    let (w, h) = (i32::from(size.0), i32::from(size.1));
+    let b = i32::from(BORDER);
+    let (left, right) = (x < b, x >= w - b);
+    let (top, bottom) = (y < b, y >= h - b);
+    let (near_left, near_right) = (x < CORNER, x >= w - CORNER);
+    let (near_top, near_bottom) = (y < CORNER, y >= h - CORNER);
+
+    let edge = if (left && near_top) || (top && near_left) {
+        Some(TopLeft)
+    } else if (right && near_top) || (top && near_right) {
+        Some(TopRight)
+    } else if (left && near_bottom) || (bottom && near_left) {
+        Some(BottomLeft)
+    } else if (right && near_bottom) || (bottom && near_right) {
+        Some(BottomRight)
+    } else if top {
+        Some(Top)
+    } else if bottom {
+        Some(Bottom)
+    } else if left {
+        Some(Left)
+    } else if right {
+        Some(Right)
+    } else {
+        None
+    };
+    if let Some(edge) = edge {
+        return FrameHit::Edge(edge);
+    }
+    if y >= i32::from(TITLE_HEIGHT) {
+        return FrameHit::Content;
+    }
+    let button = (w - b - x) / i32::from(TITLE_HEIGHT);
+    match BUTTONS.get(button as usize) {
+        Some(button) => FrameHit::Button(*button),
+        None => FrameHit::TitleBar,
+    }
+}
+
+/// What the backend needs the frame to look like.
+pub(crate) struct FrameInfo<'a> {
+    /// Size of the whole surface, in pixels.
+    pub(crate) size: (u16, u16),
+    pub(crate) title: &'a str,
+    pub(crate) active: bool,
+    pub(crate) hover: Option<FrameButton>,
+}
+
+const VERT: &str = "#version 100
+precision mediump float;
+uniform mat4 cam;
+attribute vec2 pos;
+attribute vec2 texpos;
+varying vec2 texcoord;
+void main() {
+    texcoord = texpos;
+    gl_Position = cam * vec4(pos, 0.0, 1.0);
+}
+\0";
+
+const FRAG: &str = "#version 100
+precision mediump float;
+uniform sampler2D graphic;
+varying vec2 texcoord;
+void main() {
+    gl_FragColor = texture2D(graphic, texcoord);
+}
+\0";
+
+/// GPU resources for drawing the frame of a window.
+pub(crate) struct Frame {
+    shader: Shader,
+    group: Group,
+    // The title bar, which borders also get their color from.
+    raster: RasterId,
+    // What's currently in `group` & `raster`.
+    size: (u16, u16),
+    title: String,
+    active: bool,
+    hover: Option<FrameButton>,
+    style: Option<FrameStyle>,
+}
+
+impl Frame {
+    pub(crate) fn new(draw: &mut dyn Draw) -> Self {
+        let shader = Shader(draw.shader_new(ShaderBuilder {
+            tint: false,
+            gradient: false,
+            graphic: true,
+            depth: false,
+            blend: false,
+            opengl_frag: FRAG,
+            opengl_vert: VERT,
+        }));
+        let group = Group(draw.group_new());
+        let raster = RasterId(draw.graphic(&[0; 4], 1, 1));
+
+        Frame {
+            shader,
+            group,
+            raster,
+            size: (0, 0),
+            title: String::new(),
+            active: false,
+            hover: None,
+            style: None,
+        }
+    }
+
+    /// Draw the frame over the edges of the surface.
+    pub(crate) fn draw(
+        &mut self,
+        draw: &mut dyn Draw,
+        info: FrameInfo<'_>,
+        style: &FrameStyle,
+    ) {
+        if info.size != self.size {
+            self.size = info.size;
+            self.group = Group(draw.group_new());
+            let shape = self.shape();
+            self.group.write((0, 0), &shape, &Transform::new());
+            self.style = None;
+        }
+        if info.title != self.title
+            || info.active != self.active
+            || info.hover != self.hover
+            || Some(*style) != self.style
+        {
+            self.title = info.title.to_string();
+            self.active = info.active;
+            self.hover = info.hover;
+            self.style = Some(*style);
+            let pixels = self.render(style);
+            self.raster.0.resize(&pixels, self.size.0.into());
+        }
+        draw.overlay(
+            self.size,
+            &*self.shader.0,
+            &*self.group.0,
+            &*self.raster.0,
+        );
+    }
+
+    // Build the title bar & border rectangles.
+    fn shape(&mut self) -> crate::Shape {
+        let (w, h) = (f32::from(self.size.0), f32::from(self.size.1));
+        let (b, t) = (f32::from(BORDER), f32::from(TITLE_HEIGHT));
+        // Borders use the top left pixel of the title bar.
+        let (u, v) = (0.5 / w, 0.5 / t);
+        let rects = [
+            ([0.0, 0.0, w, t], [0.0, 0.0, 1.0, 1.0]),
+            ([0.0, t, b, h], [u, v, u, v]),
+            ([w - b, t, w, h], [u, v, u, v]),
+            ([b, h - b, w - b, h], [u, v, u, v]),
+        ];
+        let mut vertices = Vec::new();
+        for ([x0, y0, x1, y1], [u0, v0, u1, v1]) in rects.iter().cloned() {
+            // Window coordinates are in widths from the top left.
+            let (x0, y0, x1, y1) = (x0 / w, y0 / w, x1 / w, y1 / w);
+            vertices.extend_from_slice(&[
+                x0, y1, u0, v1, x1, y1, u1, v1, x1, y0, u1, v0, x0, y0, u0, v0,
+                x0, y1, u0, v1, x1, y0, u1, v0,
+            ]);
+        }
+        ShapeBuilder::new(&mut self.shader)
+            .vert(&vertices)
+            .face(Transform::new())
+            .finish()
+    }
+
+    // Render the title bar pixels.
+    fn render(&self, style: &FrameStyle) -> Vec<u8> {
+        let width = usize::from(self.size.0);
+        let height = usize::from(TITLE_HEIGHT);
+        let mut pixels = vec![0; width * height * 4];
+        let mut fill =
+            |x0: usize, y0: usize, x1: usize, y1: usize, c: [u8; 4]| {
+                for y in y0..y1.min(height) {
+                    for x in x0..x1.min(width) {
+                        let i = (y * width + x) * 4;
+                        pixels[i..i + 4].copy_from_slice(&c);
+                    }
+                }
+            };
+
+        // Background
+        let background = if self.active {
+            style.active
+        } else {
+            style.inactive
+        };
+        fill(0, 0, width, height, rgba(background));
+
+        // Buttons
+        let text = rgba(style.text);
+        let size = usize::from(TITLE_HEIGHT);
+        let mut buttons_x = width.saturating_sub(usize::from(BORDER));
+        for button in BUTTONS.iter().cloned() {
+            buttons_x = buttons_x.saturating_sub(size);
+            let x = buttons_x;
+            if self.hover == Some(button) {
+                let hover = if button == FrameButton::Close {
+                    style.close_hover
+                } else {
+                    style.hover
+                };
+                fill(x, 0, x + size, size, rgba(hover));
+            }
+            // 10x10 icon in the middle of the button.
+            let (x, y) = (x + (size - 10) / 2, (size - 10) / 2);
+            match button {
+                FrameButton::Minimize => fill(x, y + 8, x + 10, y + 10, text),
+                FrameButton::Maximize => {
+                    fill(x, y, x + 10, y + 2, text);
+                    fill(x, y + 9, x + 10, y + 10, text);
+                    fill(x, y, x + 1, y + 10, text);
+                    fill(x + 9, y, x + 10, y + 10, text);
+                }
+                FrameButton::Close => {
+                    for i in 0..10 {
+                        fill(x + i, y + i, x + i + 1, y + i + 1, text);
+                        fill(x + 9 - i, y + i, x + 10 - i, y + i + 1, text);
+                        if i < 9 {
+                            fill(x + i + 1, y + i, x + i + 2, y + i + 1, text);
+                            fill(x + 8 - i, y + i, x + 9 - i, y + i + 1, text);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Title (cut off before the buttons)
+        let advance = (font::WIDTH + 1) * SCALE;
+        let mut x = usize::from(BORDER) + 8;
+        let y = (height - font::HEIGHT * SCALE) / 2;
+        for c in self.title.chars() {
+            if x + font::WIDTH * SCALE + 8 > buttons_x {
+                break;
+            }
+            for (row, bits) in font::glyph(c).iter().enumerate() {
+                for col in 0..font::WIDTH {
+                    if bits & (1 << (font::WIDTH - 1 - col)) != 0 {
+                        let (px, py) = (x + col * SCALE, y + row * SCALE);
+                        fill(px, py, px + SCALE, py + SCALE, text);
+                    }
+                }
+            }
+            x += advance;
+        }
+
+        pixels
+    }
+}
+
+fn rgba(color: [f32; 3]) -> [u8; 4] {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0) as u8;
+
+    [channel(color[0]), channel(color[1]), channel(color[2]), 255]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hit, FrameButton, FrameHit};
+    use crate::ResizeEdge::*;
+
+    const SIZE: (u16, u16) = (200, 100);
+
+    #[test]
+    fn corners() {
+        assert_eq!(hit(SIZE, 0, 0), FrameHit::Edge(TopLeft));
+        assert_eq!(hit(SIZE, 199, 0), FrameHit::Edge(TopRight));
+        assert_eq!(hit(SIZE, 0, 99), FrameHit::Edge(BottomLeft));
+        assert_eq!(hit(SIZE, 199, 99), FrameHit::Edge(BottomRight));
+    }
+
+    #[test]
+    fn corners_extend_along_edges() {
+        assert_eq!(hit(SIZE, 15, 0), FrameHit::Edge(TopLeft));
+        assert_eq!(hit(SIZE, 0, 15), FrameHit::Edge(TopLeft));
+        assert_eq!(hit(SIZE, 184, 3), FrameHit::Edge(TopRight));
+        assert_eq!(hit(SIZE, 196, 84), FrameHit::Edge(BottomRight));
+        assert_eq!(hit(SIZE, 16, 0), FrameHit::Edge(Top));
+        assert_eq!(hit(SIZE, 0, 16), FrameHit::Edge(Left));
+    }
+
+    #[test]
+    fn edges() {
+        assert_eq!(hit(SIZE, 100, 0), FrameHit::Edge(Top));
+        assert_eq!(hit(SIZE, 100, 3), FrameHit::Edge(Top));
+        assert_eq!(hit(SIZE, 100, 99), FrameHit::Edge(Bottom));
+        assert_eq!(hit(SIZE, 100, 96), FrameHit::Edge(Bottom));
+        assert_eq!(hit(SIZE, 0, 50), FrameHit::Edge(Left));
+        assert_eq!(hit(SIZE, 3, 50), FrameHit::Edge(Left));
+        assert_eq!(hit(SIZE, 199, 50), FrameHit::Edge(Right));
+        assert_eq!(hit(SIZE, 196, 50), FrameHit::Edge(Right));
+    }
+
+    #[test]
+    fn title_bar_and_buttons() {
+        assert_eq!(hit(SIZE, 100, 4), FrameHit::TitleBar);
+        assert_eq!(hit(SIZE, 100, 27), FrameHit::TitleBar);
+        assert_eq!(hit(SIZE, 195, 10), FrameHit::Button(FrameButton::Close));
+        assert_eq!(hit(SIZE, 169, 10), FrameHit::Button(FrameButton::Close));
+        assert_eq!(hit(SIZE, 168, 10), FrameHit::Button(FrameButton::Maximize));
+        assert_eq!(hit(SIZE, 140, 10), FrameHit::Button(FrameButton::Minimize));
+        assert_eq!(hit(SIZE, 112, 10), FrameHit::TitleBar);
+    }
+
+    #[test]
+    fn content() {
+        assert_eq!(hit(SIZE, 100, 28), FrameHit::Content);
+        assert_eq!(hit(SIZE, 4, 50), FrameHit::Content);
+        assert_eq!(hit(SIZE, 195, 50), FrameHit::Content);
+        assert_eq!(hit(SIZE, 100, 95), FrameHit::Content);
+    }
+}
@@ -16,7 +16,9 @@ use std::task::{Context, Poll};
 
 pub use human::Input;
 
-struct InputListener<T>
+use crate::WindowId;
+
+struct WindowInputListener<T>
 where
     T: Future<Output = Input>,
 {
@@ -24,31 +26,58 @@ where
     additional: crate::ffi::InputListener,
 }
 
+struct InputListener<T>(WindowInputListener<T>)
+where
+    T: Future<Output = Input>;
+
 /// Get an input listener that gets additional input reported by the window.
+/// Window input only reaches listeners on the thread the window is on.
 pub fn input() -> impl Future<Output = Input> + Unpin {
-    let original = Input::listener();
-    let additional = crate::ffi::InputListener;
+    InputListener(WindowInputListener {
+        original: Input::listener(),
+        additional: crate::ffi::InputListener,
+    })
+}
 
-    InputListener {
-        original,
-        additional,
+/// Get an input listener like `input()`, that also reports which window the
+/// input was sent to (`None` for input that isn't from a window, like game
+/// controllers).
+pub fn input_with_window(
+) -> impl Future<Output = (Option<WindowId>, Input)> + Unpin {
+    WindowInputListener {
+        original: Input::listener(),
+        additional: crate::ffi::InputListener,
     }
 }
 
-impl<T> Future for InputListener<T>
+impl<T> Future for WindowInputListener<T>
 where
     T: Future<Output = Input> + Unpin,
 {
-    type Output = Input;
+    type Output = (Option<WindowId>, Input);
 
-    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Input> {
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
         let original = Pin::new(&mut self.original);
 
         if let Poll::Ready(input) = original.poll(cx) {
-            Poll::Ready(input)
+            Poll::Ready((None, input))
         } else {
             let additional = Pin::new(&mut self.additional);
-            additional.poll(cx)
+            additional.poll(cx).map(|(id, input)| (Some(id), input))
         }
     }
 }
+
+impl<T> Future for InputListener<T>
+where
+    T: Future<Output = Input> + Unpin,
+{
+    type Output = Input;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Input> {
+        Pin::new(&mut self.0).poll(cx).map(|(_id, input)| input)
+    }
+}
@@ -19,9 +19,11 @@
 //! Vulkan first, too) if it's installed, and fall back to OpenGL if it's not
 //! installed.
 //!
-//! Since this crate is minimal, it doesn't even handle window decoration.  If
-//! you want window decoration, GUI widgets, and other hardware abstractions
-//! check out [cala](https://crates.io/crates/cala).
+//! Since this crate is minimal, window decorations are left to the window
+//! manager, with an optional plain built-in frame (`WindowBuilder::frame()`)
+//! for window managers that don't draw them.  If you want fancier window
+//! decoration, GUI widgets, and other hardware abstractions check out
+//! [cala](https://crates.io/crates/cala).
 //!
 //! # Coordinate System
 //! ![](https://raw.githubusercontent.com/libcala/window/5205e59f0cd9f37a619f590e94218900afc2395b/res/coordinate_system.svg)
@@ -36,6 +38,8 @@
 )]
 
 use std::ffi::c_void;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
 use whoami::{desktop_env, DesktopEnv};
 
 /// Load a generated shader from the `res` crate.
@@ -46,9 +50,14 @@ macro_rules! shader {
     };
 }
 
+mod builder;
+mod error;
+mod event;
 mod ffi;
+mod frame;
 pub mod input;
 mod mat4;
+mod region;
 mod shape;
 
 #[cfg(unix)]
@@ -57,7 +66,12 @@ mod wayland;
 #[cfg(not(any(target_os = "macos", target_os = "ios")))]
 mod opengl;
 
+pub use self::builder::*;
+pub use self::error::*;
+pub use self::event::*;
+pub use self::frame::FrameStyle;
 pub use self::mat4::*;
+pub use self::region::*;
 pub use self::shape::*;
 
 /// Native Window Handle.
@@ -111,23 +125,105 @@ fn library() -> Option<LinGLib> {
     }
 }
 
-trait Nwin {
-    /// Get a pointer that refers to this window for interfacing.
+/// Per-frame callback for a window.
+type Redraw = Box<dyn FnMut(&mut Window, std::time::Duration)>;
+
+/// Hit test callback for a window.
+type HitTest = Box<dyn FnMut(f32, f32) -> Region>;
+
+/// Identifies a window, for telling apart input from different windows.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WindowId(u32);
+
+impl WindowId {
+    fn new() -> Self {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+
+        WindowId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+trait Napp {
+    /// Get a pointer that refers to the connection for interfacing.
     fn handle(&self) -> NwinHandle;
+    /// Create a new window on this connection.
+    fn window(
+        self: Rc<Self>,
+        id: WindowId,
+        builder: &WindowBuilder,
+        redraw: Redraw,
+    ) -> Result<Box<dyn Nwin>, WindowError>;
+}
+
+trait Ncontext {
+    /// Create drawing for one window, sharing this graphics context.
+    fn draw(self: Rc<Self>) -> Box<dyn Draw>;
+}
+
+trait Nwin {
     /// Connect window to the drawing context.
     #[allow(clippy::borrowed_box)] // Nope, this is actually required
-    fn connect(&mut self, draw: &mut Box<dyn Draw>);
+    fn connect(&mut self, draw: &mut Box<dyn Draw>) -> Result<(), WindowError>;
     /// Get the next frame.  Return false on quit.
     fn run(&mut self, window: *mut crate::Window) -> bool;
     /// Get the window width & height.
     fn dimensions(&self) -> (u16, u16);
+    /// Get the next event sent to this window.
+    fn event(&mut self) -> Option<WindowEvent>;
+    /// Make `run()` return false.
+    fn close(&mut self);
+    /// Request fullscreen on or off.
+    fn set_fullscreen(&mut self, fullscreen: bool);
+    /// Request maximized on or off.
+    fn set_maximized(&mut self, maximized: bool);
+    /// Request minimize.
+    fn minimize(&mut self);
+    /// Get the state last reported by the window manager.
+    fn status(&self) -> WindowStatus;
+    /// Change the window title.
+    fn set_title(&mut self, title: &str) -> Result<(), WindowError>;
+    /// Change the app id.
+    fn set_app_id(&mut self, app_id: &str) -> Result<(), WindowError>;
+    /// Start resizing from an edge, with the latest button press.
+    fn begin_resize(&mut self, edge: ResizeEdge);
+    /// Show the window manager's window menu, with the latest button press.
+    fn show_window_menu(&mut self, x: f32, y: f32);
+    /// Lock the pointer in place, or confine it to the window.
+    fn lock_pointer(&mut self, confine: bool);
+    /// Remove the pointer lock or confinement, with a cursor position hint.
+    fn unlock_pointer(&mut self, hint: Option<(f32, f32)>);
+    /// Ask for decorations to be drawn by the client or the server.
+    fn set_decorations(&mut self, decorations: Decorations);
+    /// Get who draws decorations.
+    fn decorations(&self) -> Decorations;
+    /// Set the function that decides which parts of the window move it.
+    fn set_hit_test(&mut self, hit_test: Option<HitTest>);
+    /// Check if Caps Lock is on.
+    fn caps_lock(&self) -> bool;
+    /// Check if Num Lock is on.
+    fn num_lock(&self) -> bool;
+    /// Set whether the built-in frame should be drawn (when it's up to us).
+    fn set_frame(&mut self, frame: bool);
+    /// Get what the built-in frame should look like, if it's drawn.
+    fn frame(&self) -> Option<frame::FrameInfo<'_>>;
+    /// Turn text input (from an input method) on or off.
+    fn set_text_input(&mut self, enabled: bool);
+    /// Set where the text cursor is, in window units.
+    fn set_text_cursor(&mut self, x: f32, y: f32, width: f32, height: f32);
+    /// Set the text around the cursor, with byte offsets.
+    fn set_surrounding_text(
+        &mut self,
+        text: &str,
+        cursor: usize,
+        anchor: usize,
+    ) -> Result<(), WindowError>;
 }
 
 trait Draw {
     /// Get a pointer that refers to this graphics context for interfacing.
     fn handle(&self) -> DrawHandle;
     /// Finish initializing graphics context.
-    fn connect(&mut self, connection: *mut c_void);
+    fn connect(&mut self, connection: *mut c_void) -> Result<(), WindowError>;
     /// Begin draw (clear screen).
     fn begin_draw(&mut self);
     /// Redraw on the screen.
@@ -155,6 +251,16 @@ trait Draw {
     fn tint(&mut self, shader: &dyn Nshader, tint: [f32; 4]);
     /// Window resize
     fn resize(&mut self, width: u16, height: u16);
+    /// Move the drawing area from the bottom left corner of the surface.
+    fn offset(&mut self, x: u16, y: u16);
+    /// Draw a graphic on top of the whole surface, without the camera.
+    fn overlay(
+        &mut self,
+        size: (u16, u16),
+        shader: &dyn Nshader,
+        shape: &dyn Ngroup,
+        graphic: &dyn Ngraphic,
+    );
 }
 
 trait Nshader {
@@ -270,81 +376,338 @@ pub struct ShaderBuilder {
     pub opengl_vert: &'static str,
 }
 
+/// A connection to the window manager, and a graphics context that's shared
+/// between all of its windows.
+///
+/// Shaders, groups and rasters created with one window of an `Application`
+/// may be used to draw on any of its other windows.
+#[derive(Clone)]
+pub struct Application {
+    // Graphics context must be dropped before the connection it uses.
+    ctx: Rc<dyn Ncontext>,
+    napp: Rc<dyn Napp>,
+}
+
+impl Application {
+    /// Connect to the window manager and initialize graphics.
+    pub fn new() -> Result<Self, WindowError> {
+        // Hopefully find a backend.
+        let napp = wayland::WaylandApp::new()?;
+
+        // Try to initialize OpenGL(ES).
+        let ctx = opengl::Context::new(napp.handle())?;
+
+        Ok(Application { ctx, napp })
+    }
+
+    /// Open a new window, calling `run` once per frame.
+    pub fn window<F>(
+        &self,
+        builder: WindowBuilder,
+        run: F,
+    ) -> Result<Window, WindowError>
+    where
+        F: FnMut(&mut Window, std::time::Duration) + 'static,
+    {
+        Window::with_app(self.clone(), &builder, Box::new(run))
+    }
+}
+
 /// A window on the monitor.
 pub struct Window {
-    // toolbar_graphic: Graphic,
-    // toolbar_shader: Shader,
-    // toolbar_shape: Group,
-    // toolbar_callback: fn(&mut [u8], u16),
-    // /// Height of the toolbar.
-    // pub toolbar_height: u16,
+    // Built-in frame (created on first use), which must be dropped before
+    // the drawing context.
+    frame_style: Option<FrameStyle>,
+    frame: Option<frame::Frame>,
+    // Drawing context must be dropped before the window it draws on.
     draw: Box<dyn Draw>,
     nwin: Box<dyn Nwin>,
+    app: Application,
+    id: WindowId,
 }
 
 impl Window {
     /// Start the Wayland + OpenGL application.
-    pub fn new(
-        name: &str,
-        run: fn(window: &mut Window, elapsed: std::time::Duration) -> (),
-    ) -> Self {
+    ///
+    /// `run` is called once per frame.  Since it may be any `FnMut` closure,
+    /// application state (shaders, groups, rasters) can be moved into it.
+    ///
+    /// # Panics
+    /// If the window can't be created.  Use `Window::try_new()` to handle the
+    /// error instead.
+    pub fn new<F>(name: &str, run: F) -> Self
+    where
+        F: FnMut(&mut Window, std::time::Duration) + 'static,
+    {
+        Self::try_new(name, run).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Start the Wayland + OpenGL application, returning an error if the
+    /// window can't be created.
+    pub fn try_new<F>(name: &str, run: F) -> Result<Self, WindowError>
+    where
+        F: FnMut(&mut Window, std::time::Duration) + 'static,
+    {
+        WindowBuilder::new(name).build(run)
+    }
+
+    /// Create a window from a `WindowBuilder`, with its own `Application`.
+    fn with_builder(
+        builder: &WindowBuilder,
+        run: Redraw,
+    ) -> Result<Self, WindowError> {
+        Self::with_app(Application::new()?, builder, run)
+    }
+
+    /// Create a window from a `WindowBuilder` on an `Application`.
+    fn with_app(
+        app: Application,
+        builder: &WindowBuilder,
+        run: Redraw,
+    ) -> Result<Self, WindowError> {
+        let id = WindowId::new();
+
+        // Title and app id are sent to the window manager as C strings.
+        let app_id = builder.app_id.as_deref().unwrap_or_default();
+        if builder.title.contains('\0') || app_id.contains('\0') {
+            return Err(WindowError::InvalidString);
+        }
+
         /*********************/
         /* Create The Window */
         /*********************/
 
-        // Hopefully find a backend.
-        let mut nwin = Err("No backends built!".to_string())
-            .or_else(|_| wayland::Wayland::new(name, run))
-            .map_err(|e| format!("Couldn't find a window manager: {}", e))
-            .unwrap();
+        let mut nwin = app.napp.clone().window(id, builder, run)?;
 
         /**********************/
         /* Initialize Drawing */
         /**********************/
 
-        // Try to initialize OpenGL(ES).
-        let mut draw = None
-            .or_else(|| opengl::OpenGL::new(&mut *nwin))
-            .expect("Couldn't find a GPU library.");
+        let mut draw = app.ctx.clone().draw();
 
         /****************************/
         /* Connect Window & Drawing */
         /****************************/
 
-        nwin.connect(&mut draw);
+        nwin.connect(&mut draw)?;
 
-        /**********************/
-        /* Initialize Toolbar */
-        /**********************/
-
-        /* unsafe { (*window.as_mut_ptr()).toolbar_height = 48; }
-
-        let (toolbar_shader, toolbar_shape) = unsafe { (toolbar)(&mut *window.as_mut_ptr()) };
-        let width = unsafe { (*window.as_mut_ptr()).nwin.dimensions().0 };
-        let height = unsafe { (*window.as_mut_ptr()).toolbar_height };
-        let pixels = unsafe { vec![255; (width * (*window.as_mut_ptr()).toolbar_height) as usize * 4] };
-        let toolbar_graphic = unsafe {
-            (*window.as_mut_ptr()).graphic(pixels.as_slice(), width as usize, height as usize) };
-        fn toolbar_callback(_: &mut [u8], _: u16) {}
-
-        unsafe {
-            std::ptr::write(&mut (*window.as_mut_ptr()).toolbar_shader, toolbar_shader);
-            std::ptr::write(&mut (*window.as_mut_ptr()).toolbar_shape, toolbar_shape);
-            std::ptr::write(&mut (*window.as_mut_ptr()).toolbar_graphic, toolbar_graphic);
-            std::ptr::write(&mut (*window.as_mut_ptr()).toolbar_callback, toolbar_callback);
-        }
+        Ok(Window {
+            frame_style: builder.frame,
+            frame: None,
+            draw,
+            nwin,
+            app,
+            id,
+        })
+    }
 
-        unsafe { std::mem::transmute(window) }*/
+    /// Get the identifier for this window.
+    pub fn id(&self) -> WindowId {
+        self.id
+    }
 
-        Window { nwin, draw }
+    /// Get the application this window belongs to, for opening more windows.
+    pub fn application(&self) -> &Application {
+        &self.app
     }
 
-    /// Run the next frame in the window.
+    /// Run the next frame in the window.  The window's redraw callback is
+    /// only ever called from inside its own `run()`.
     pub fn run(&mut self) -> bool {
         let this: *mut _ = self;
         self.nwin.run(this)
     }
 
+    /// Get the next event sent to this window, in the order they were
+    /// received.  Call this in a loop (from the frame callback, or after
+    /// `run()`) until it returns `None`.
+    pub fn event(&mut self) -> Option<WindowEvent> {
+        self.nwin.event()
+    }
+
+    /// Close the window: the next call to `run()` returns false.
+    pub fn close(&mut self) {
+        self.nwin.close()
+    }
+
+    /// Ask the window manager to make the window fullscreen, or to restore
+    /// it from fullscreen.
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.nwin.set_fullscreen(fullscreen)
+    }
+
+    /// Ask the window manager to maximize the window, or to restore it from
+    /// being maximized.
+    pub fn set_maximized(&mut self, maximized: bool) {
+        self.nwin.set_maximized(maximized)
+    }
+
+    /// Ask the window manager to minimize the window.  There's no way to
+    /// restore a minimized window from the app; the user has to do it.
+    pub fn minimize(&mut self) {
+        self.nwin.minimize()
+    }
+
+    /// Check if the window is fullscreen.
+    pub fn is_fullscreen(&self) -> bool {
+        self.nwin.status().fullscreen
+    }
+
+    /// Check if the window is maximized.
+    pub fn is_maximized(&self) -> bool {
+        self.nwin.status().maximized
+    }
+
+    /// Get the state of the window, as last reported by the window manager.
+    /// Changes are also sent as `WindowEvent::StateChanged`.
+    pub fn status(&self) -> WindowStatus {
+        self.nwin.status()
+    }
+
+    /// Change the window title.  Fails with `WindowError::InvalidString` if
+    /// `title` contains a NUL byte.
+    pub fn set_title(&mut self, title: &str) -> Result<(), WindowError> {
+        self.nwin.set_title(title)
+    }
+
+    /// Change the app id, used to match the window to its `.desktop` file.
+    /// Fails with `WindowError::InvalidString` if `app_id` contains a NUL
+    /// byte.
+    pub fn set_app_id(&mut self, app_id: &str) -> Result<(), WindowError> {
+        self.nwin.set_app_id(app_id)
+    }
+
+    /// Ask the window manager to change who draws the window decorations.
+    /// Changes are sent as `WindowEvent::DecorationsChanged`.
+    pub fn set_decorations(&mut self, decorations: Decorations) {
+        self.nwin.set_decorations(decorations)
+    }
+
+    /// Get who draws the window decorations.  If the window manager doesn't
+    /// support choosing, this is always `Decorations::Client`.
+    pub fn decorations(&self) -> Decorations {
+        self.nwin.decorations()
+    }
+
+    /// Check if Caps Lock is on (as of the latest keyboard input).
+    pub fn caps_lock(&self) -> bool {
+        self.nwin.caps_lock()
+    }
+
+    /// Check if Num Lock is on (as of the latest keyboard input).
+    pub fn num_lock(&self) -> bool {
+        self.nwin.num_lock()
+    }
+
+    /// Change the style of the built-in frame, or remove it with `None`.  The
+    /// frame is only drawn when the decorations are `Decorations::Client`,
+    /// and isn't part of the window's size or pointer coordinates.
+    pub fn set_frame(&mut self, style: Option<FrameStyle>) {
+        self.frame_style = style;
+        self.nwin.set_frame(style.is_some())
+    }
+
+    /// Turn text input from the input method (IME) on or off, for example
+    /// when a text field gains or loses focus.  While on, composed text is
+    /// sent as `WindowEvent::Preedit` and committed text as `Input::Text`.
+    pub fn set_text_input(&mut self, enabled: bool) {
+        self.nwin.set_text_input(enabled)
+    }
+
+    /// Tell the input method where the text cursor is, so it can place its
+    /// popups next to it.  Uses the same units as `Input::PointerX` and
+    /// `Input::PointerY`.
+    pub fn set_text_cursor(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.nwin.set_text_cursor(x, y, width, height)
+    }
+
+    /// Tell the input method about the text around the cursor, with the
+    /// cursor and selection anchor as byte offsets into `text`.  Fails with
+    /// `WindowError::InvalidString` if `text` contains a NUL byte or isn't
+    /// under 4000 bytes, or if an offset isn't on a character boundary.
+    pub fn set_surrounding_text(
+        &mut self,
+        text: &str,
+        cursor: usize,
+        anchor: usize,
+    ) -> Result<(), WindowError> {
+        self.nwin.set_surrounding_text(text, cursor, anchor)
+    }
+
+    /// Draw the built-in frame over the edges of the surface, if needed.
+    pub(crate) fn draw_frame(&mut self) {
+        let style = match self.frame_style {
+            Some(style) => style,
+            None => return,
+        };
+        let info = match self.nwin.frame() {
+            Some(info) => info,
+            None => return,
+        };
+        let draw = &mut self.draw;
+        let frame = self
+            .frame
+            .get_or_insert_with(|| frame::Frame::new(&mut **draw));
+        frame.draw(&mut **draw, info, &style);
+    }
+
+    /// Set a function that decides what each point on the window is used for,
+    /// so that apps drawing their own title bar and borders can let the user
+    /// move and resize the window.  It's called with the pointer position (in
+    /// the same coordinates as `Input::PointerX` and `Input::PointerY`).
+    ///
+    /// Without a hit test, the whole window is `Region::Client`.
+    pub fn set_hit_test<F>(&mut self, hit_test: F)
+    where
+        F: FnMut(f32, f32) -> Region + 'static,
+    {
+        self.nwin.set_hit_test(Some(Box::new(hit_test)))
+    }
+
+    /// Start resizing the window from an edge or corner, following the
+    /// pointer (or touch) until the button is released.  Call this right
+    /// after receiving the button press (or touch) that starts the resize.
+    pub fn begin_resize(&mut self, edge: ResizeEdge) {
+        self.nwin.begin_resize(edge)
+    }
+
+    /// Show the window manager's window menu (with items like minimize and
+    /// close) at a point on the window, in the same coordinates as
+    /// `Input::PointerX` and `Input::PointerY`.  Call this right after
+    /// receiving the button press that opens the menu.
+    pub fn show_window_menu(&mut self, x: f32, y: f32) {
+        self.nwin.show_window_menu(x, y)
+    }
+
+    /// Lock the pointer in place while it's over the window (for example, to
+    /// control a first-person camera), until `unlock_pointer()`.  While it's
+    /// locked, use `WindowEvent::RelativeMotion` for movement.  Changes are
+    /// sent as `WindowEvent::PointerLockChanged`.  Does nothing if the window
+    /// manager doesn't support it.
+    pub fn lock_pointer(&mut self) {
+        self.nwin.lock_pointer(false)
+    }
+
+    /// Keep the pointer from leaving the window, until `unlock_pointer()`.
+    /// Changes are sent as `WindowEvent::PointerLockChanged`.  Does nothing
+    /// if the window manager doesn't support it.
+    pub fn confine_pointer(&mut self) {
+        self.nwin.lock_pointer(true)
+    }
+
+    /// Undo `lock_pointer()` or `confine_pointer()`.  For a locked pointer,
+    /// `hint` is where the cursor should appear (in the same coordinates as
+    /// `Input::PointerX` and `Input::PointerY`), if the window manager
+    /// supports it.
+    pub fn unlock_pointer(&mut self, hint: Option<(f32, f32)>) {
+        self.nwin.unlock_pointer(hint)
+    }
+
+    /// Remove the hit test function, so the whole window is client area.
+    pub fn clear_hit_test(&mut self) {
+        self.nwin.set_hit_test(None)
+    }
+
     /// Change the background color.
     pub fn background(&mut self, r: f32, g: f32, b: f32) {
         self.draw.background(r, g, b)
@@ -413,9 +776,3 @@ impl Window {
         h / w
     }
 }
-
-impl Drop for Window {
-    fn drop(&mut self) {
-        std::process::exit(0);
-    }
-}
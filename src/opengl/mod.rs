@@ -11,6 +11,7 @@
 use std::cell::Cell;
 use std::collections::HashMap;
 use std::ffi::c_void;
+use std::rc::Rc;
 
 use super::Draw;
 use super::DrawHandle;
@@ -18,6 +19,7 @@ use crate::Ngraphic;
 use crate::Ngroup;
 use crate::Nshader;
 use crate::Transform;
+use crate::WindowError;
 
 mod platform;
 
@@ -99,6 +101,8 @@ extern "C" {
         read: *mut c_void,
         ctx: *mut c_void,
     ) -> u32;
+    fn eglDestroySurface(dpy: *mut c_void, surface: *mut c_void) -> u32;
+    fn eglDestroyContext(dpy: *mut c_void, ctx: *mut c_void) -> u32;
     fn eglTerminate(dpy: *mut c_void) -> u32;
     fn eglReleaseThread() -> u32;
     fn eglSwapBuffers(dpy: *mut c_void, surface: *mut c_void) -> u32;
@@ -652,37 +656,35 @@ impl Ngraphic for Graphic {
     }
 }
 
+const IDENTITY: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
 struct ShaderData {
     dirty_transform: bool, // If matrix needs to be updated.
     matrix: [[f32; 4]; 4], // Transform matrix, minus coordinate system.
 }
 
-pub struct OpenGL {
-    surface: *mut c_void,
+// EGL display & context, shared between all windows of an application.
+pub(super) struct Context {
     display: *mut c_void,
     context: *mut c_void,
     config: *mut c_void,
-    graphic: u32,
-    depth: bool,
-    blending: bool,
-    shader: u32,
-    shape_id: u32,
-    vaa_col: bool,
-    vaa_tex: bool,
-    shaders: HashMap<u32, ShaderData>,
-    cam: Transform,
-    height: f32,
-    near: f32,
-    horizon: f32,
+    // The surface that's currently bound to the context.
+    current: Cell<*mut c_void>,
 }
 
-impl OpenGL {
+impl Context {
     #[cfg(unix)]
-    #[allow(clippy::new_ret_no_self)] // It's basically Self, in a weird way.
-    pub(super) fn new(nwin: &mut dyn crate::Nwin) -> Option<Box<dyn Draw>> {
+    pub(super) fn new(
+        handle: crate::NwinHandle,
+    ) -> Result<Rc<Self>, WindowError> {
         let (display, config, context) = unsafe {
             // Get EGL Display from Window.
-            let display = eglGetDisplay(match nwin.handle() {
+            let display = eglGetDisplay(match handle {
                 #[cfg(not(any(
                     target_os = "android",
                     target_os = "macos",
@@ -690,19 +692,26 @@ impl OpenGL {
                 )))]
                 crate::NwinHandle::Wayland(handle) => handle,
             });
-            debug_assert!(!display.is_null());
+            if display.is_null() {
+                return Err(WindowError::GraphicsInit);
+            }
 
             // Initialize EGL Display.
             let mut major = std::mem::MaybeUninit::uninit();
             let mut minor = std::mem::MaybeUninit::uninit();
             let ret =
                 eglInitialize(display, major.as_mut_ptr(), minor.as_mut_ptr());
-            debug_assert_eq!(ret, 1);
+            if ret != 1 {
+                return Err(WindowError::GraphicsInit);
+            }
 
             // Connect EGL to either OpenGL or OpenGLES, whichever is available.
             // TODO: also support /*OPENGL:*/ 0x30A2
             let ret = eglBindAPI(/*OPENGL_ES:*/ 0x30A0);
-            debug_assert_eq!(ret, 1);
+            if ret != 1 {
+                eglTerminate(display);
+                return Err(WindowError::GraphicsInit);
+            }
 
             //
             let mut config = std::mem::MaybeUninit::<*mut c_void>::uninit();
@@ -728,7 +737,10 @@ impl OpenGL {
                 1,
                 n.as_mut_ptr(),
             );
-            debug_assert_eq!(ret, 1);
+            if ret != 1 || n.assume_init() < 1 {
+                eglTerminate(display);
+                return Err(WindowError::GraphicsConfig);
+            }
 
             let config = config.assume_init();
 
@@ -743,19 +755,47 @@ impl OpenGL {
                 ]
                 .as_ptr(),
             );
-            debug_assert!(!context.is_null());
+            if context.is_null() {
+                eglTerminate(display);
+                return Err(WindowError::GraphicsContext);
+            }
 
             (display, config, context)
         };
 
+        Ok(Rc::new(Context {
+            display,
+            context,
+            config,
+            current: Cell::new(std::ptr::null_mut()),
+        }))
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        unsafe {
+            eglMakeCurrent(
+                self.display,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            );
+            eglDestroyContext(self.display, self.context);
+            eglTerminate(self.display);
+            eglReleaseThread();
+        }
+    }
+}
+
+impl crate::Ncontext for Context {
+    fn draw(self: Rc<Self>) -> Box<dyn Draw> {
         let height = 480.0 / 640.0;
         let near = 0.01; // 1cm
         let horizon = 5000.0; // 5km
 
-        let draw: OpenGL = OpenGL {
-            display,
-            config,
-            context,
+        Box::new(OpenGL {
+            context: self,
             surface: std::ptr::null_mut(),
             graphic: 0,
             depth: false,
@@ -769,23 +809,91 @@ impl OpenGL {
             height,
             near,
             horizon,
-        };
+            size: (0, 0),
+            offset: (0, 0),
+            background: (0.0, 0.0, 1.0),
+        })
+    }
+}
 
-        Some(Box::new(draw))
+// Drawing on one window, using the shared context.
+pub struct OpenGL {
+    context: Rc<Context>,
+    surface: *mut c_void,
+    graphic: u32,
+    depth: bool,
+    blending: bool,
+    shader: u32,
+    shape_id: u32,
+    vaa_col: bool,
+    vaa_tex: bool,
+    shaders: HashMap<u32, ShaderData>,
+    cam: Transform,
+    height: f32,
+    near: f32,
+    horizon: f32,
+    size: (u16, u16),
+    offset: (u16, u16),
+    background: (f32, f32, f32),
+}
+
+impl OpenGL {
+    // Bind this window's surface to the shared context.
+    fn make_current(&mut self) {
+        if self.surface.is_null() || self.context.current.get() == self.surface
+        {
+            return;
+        }
+        unsafe {
+            eglMakeCurrent(
+                self.context.display,
+                self.surface,
+                self.surface,
+                self.context.context,
+            );
+        }
+        self.context.current.set(self.surface);
+        self.reset();
+    }
+
+    // Forget cached GL state, since another window may have changed it.
+    fn reset(&mut self) {
+        self.graphic = 0;
+        self.shader = 0;
+        self.shape_id = u32::MAX;
+        unsafe {
+            glDisable(0x0BE2 /*BLEND*/);
+            glDisable(0x0B71 /*DEPTH_TEST*/);
+            glDisableVertexAttribArray(GL_ATTRIB_COL);
+            glDisableVertexAttribArray(GL_ATTRIB_TEX);
+        }
+        self.blending = false;
+        self.depth = false;
+        self.vaa_col = false;
+        self.vaa_tex = false;
+        // Uniforms are per-program, so may have been set by another window.
+        for shader in &mut self.shaders {
+            shader.1.dirty_transform = true;
+        }
     }
 }
 
 impl Drop for OpenGL {
     fn drop(&mut self) {
+        if self.surface.is_null() {
+            return;
+        }
         unsafe {
-            eglMakeCurrent(
-                self.display,
-                std::ptr::null_mut(),
-                std::ptr::null_mut(),
-                std::ptr::null_mut(),
-            );
-            eglTerminate(self.display);
-            eglReleaseThread();
+            if self.context.current.get() == self.surface {
+                eglMakeCurrent(
+                    self.context.display,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                );
+                self.context.current.set(std::ptr::null_mut());
+            }
+            eglDestroySurface(self.context.display, self.surface);
         }
     }
 }
@@ -796,25 +904,31 @@ impl Draw for OpenGL {
         DrawHandle::Gl(std::ptr::null_mut())
     }
 
-    fn connect(&mut self, connection: *mut c_void) {
+    fn connect(&mut self, connection: *mut c_void) -> Result<(), WindowError> {
         // Finish connecting EGL.
         self.surface = unsafe {
             eglCreateWindowSurface(
-                self.display,
-                self.config,
+                self.context.display,
+                self.context.config,
                 connection as usize,
                 std::ptr::null(),
             )
         };
+        if self.surface.is_null() {
+            return Err(WindowError::GraphicsContext);
+        }
         let ret = unsafe {
             eglMakeCurrent(
-                self.display,
+                self.context.display,
                 self.surface,
                 self.surface,
-                self.context,
+                self.context.context,
             )
         };
-        debug_assert_ne!(ret, 0);
+        if ret == 0 {
+            return Err(WindowError::GraphicsContext);
+        }
+        self.context.current.set(self.surface);
 
         // Configuration (TODO)
 
@@ -842,43 +956,44 @@ impl Draw for OpenGL {
             println!("ext: {}", slice.to_str().unwrap().contains("GL_EXT_base_instance"));
         }*/
 
-        // Set default background for OpenGL.
-        self.background(0.0, 0.0, 1.0);
+        Ok(())
     }
 
     fn background(&mut self, r: f32, g: f32, b: f32) {
-        unsafe {
-            glClearColor(r, g, b, 1.0);
-            gl_assert!("glClearColor");
-        }
+        // Applied in `begin_draw()`, since the context is shared.
+        self.background = (r, g, b);
     }
 
     fn shader_new(
         &mut self,
         builder: crate::ShaderBuilder,
     ) -> Box<dyn Nshader> {
+        self.make_current();
         let shader = Shader::new(builder);
         self.shaders.insert(
             shader.program(),
             ShaderData {
                 dirty_transform: true,
-                matrix: [
-                    [1.0, 0.0, 0.0, 0.0],
-                    [0.0, 1.0, 0.0, 0.0],
-                    [0.0, 0.0, 1.0, 0.0],
-                    [0.0, 0.0, 0.0, 1.0],
-                ],
+                matrix: IDENTITY,
             },
         );
         Box::new(shader)
     }
 
     fn group_new(&mut self) -> Box<dyn Ngroup> {
+        self.make_current();
         Box::new(Group::new())
     }
 
     fn begin_draw(&mut self) {
+        self.make_current();
         self.shape_id = std::u32::MAX;
+        unsafe {
+            let (r, g, b) = self.background;
+            glClearColor(r, g, b, 1.0);
+            gl_assert!("glClearColor");
+        }
+        self.viewport();
         unsafe {
             glClear(
                 0x0000_4000 /*GL_COLOR_BUFFER_BIT*/ | 0x0000_0100, /*GL_DEPTH_BUFFER_BIT*/
@@ -893,7 +1008,7 @@ impl Draw for OpenGL {
         unsafe { glDisableVertexAttribArray(GL_ATTRIB_POS) }
         gl_assert!("glDisableVertexAttribArray#4");
         unsafe {
-            eglSwapBuffers(self.display, self.surface);
+            eglSwapBuffers(self.context.display, self.surface);
         }
     }
 
@@ -949,7 +1064,12 @@ impl Draw for OpenGL {
             ])
         };
 
-        let shaderdata = self.shaders.get_mut(&shader.program()).unwrap();
+        // Shaders may be created with another window's drawing context.
+        let shaderdata =
+            self.shaders.entry(shader.program()).or_insert(ShaderData {
+                dirty_transform: true,
+                matrix: IDENTITY,
+            });
         if shaderdata.dirty_transform {
             let matrix = (Transform::from_mat4(shaderdata.matrix))
                 .scale(2.0, -2.0, -2.0)
@@ -1071,6 +1191,7 @@ impl Draw for OpenGL {
         width: usize,
         height: usize,
     ) -> Box<dyn Ngraphic> {
+        self.make_current();
         Box::new(Graphic::new(pixels, width, height))
     }
 
@@ -1096,6 +1217,7 @@ impl Draw for OpenGL {
 
     fn tint(&mut self, shader: &dyn Nshader, tint: [f32; 4]) {
         if let Some(a) = shader.tint() {
+            self.make_current();
             self.bind_shader(shader);
             unsafe {
                 glUniform4f(a, tint[0], tint[1], tint[2], tint[3]);
@@ -1108,16 +1230,48 @@ impl Draw for OpenGL {
         for shader in &mut self.shaders {
             shader.1.dirty_transform = true;
         }
-        // Update viewport
-        unsafe {
-            glViewport(0, 0, width.into(), height.into());
-        }
+        // Update viewport (in `begin_draw()`)
+        self.size = (width, height);
         //
         self.height = height as f32 / width as f32;
     }
+
+    fn offset(&mut self, x: u16, y: u16) {
+        // Update viewport (in `begin_draw()`)
+        self.offset = (x, y);
+    }
+
+    fn overlay(
+        &mut self,
+        size: (u16, u16),
+        shader: &dyn Nshader,
+        shape: &dyn Ngroup,
+        graphic: &dyn Ngraphic,
+    ) {
+        // Draw over the whole surface, ignoring the camera.
+        let (cam, offset, (width, height)) = (self.cam, self.offset, self.size);
+        self.cam = Transform::new();
+        self.offset = (0, 0);
+        self.resize(size.0, size.1);
+        self.viewport();
+        self.bind_graphic(graphic);
+        self.draw(shader, shape);
+        self.cam = cam;
+        self.offset = offset;
+        self.resize(width, height);
+        self.viewport();
+    }
 }
 
 impl OpenGL {
+    fn viewport(&mut self) {
+        let (x, y) = self.offset;
+        let (width, height) = self.size;
+        unsafe {
+            glViewport(x.into(), y.into(), width.into(), height.into());
+        }
+    }
+
     fn bind_shader(&mut self, shader: &dyn Nshader) -> bool {
         let shader_id = shader.program();
         if shader_id != self.shader {
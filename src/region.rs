@@ -0,0 +1,42 @@
+// Window
+// Copyright © 2019-2021 Jeron Aldaron Lau.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+/// An edge or corner of a window, for resizing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResizeEdge {
+    /// Top edge
+    Top,
+    /// Bottom edge
+    Bottom,
+    /// Left edge
+    Left,
+    /// Right edge
+    Right,
+    /// Top left corner
+    TopLeft,
+    /// Top right corner
+    TopRight,
+    /// Bottom left corner
+    BottomLeft,
+    /// Bottom right corner
+    BottomRight,
+}
+
+/// What a part of a window is used for, returned by the hit test callback
+/// set with `Window::set_hit_test()`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Region {
+    /// Clicks are sent to the app as input.
+    Client,
+    /// Dragging with the left button moves the window.
+    TitleBar,
+    /// Dragging with the left button resizes the window from this edge.
+    Edge(ResizeEdge),
+}
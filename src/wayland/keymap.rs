@@ -0,0 +1,504 @@
+// Window
+// Copyright © 2019-2021 Jeron Aldaron Lau.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+//! Keyboard layout & modifier state, from the compositor's XKB keymap.
+
+use std::{
+    ffi::CStr,
+    fs::File,
+    os::raw::{c_char, c_int, c_void},
+    os::unix::io::FromRawFd,
+    ptr::null_mut,
+};
+
+use dl_api::linker;
+use human::{Key, Mod};
+
+#[repr(C)]
+struct XkbContext {
+    _unused: [u8; 0],
+}
+
+#[repr(C)]
+struct XkbKeymap {
+    _unused: [u8; 0],
+}
+
+#[repr(C)]
+struct XkbState {
+    _unused: [u8; 0],
+}
+
+linker!(extern "C" XkbCommon "libxkbcommon.so.0" {
+    fn xkb_context_new(flags: c_int) -> *mut XkbContext;
+    fn xkb_context_unref(context: *mut XkbContext) -> ();
+    fn xkb_keymap_new_from_string(
+        context: *mut XkbContext,
+        string: *const c_char,
+        format: c_int,
+        flags: c_int,
+    ) -> *mut XkbKeymap;
+    fn xkb_keymap_unref(keymap: *mut XkbKeymap) -> ();
+    fn xkb_keymap_key_repeats(keymap: *mut XkbKeymap, key: u32) -> c_int;
+    fn xkb_keymap_mod_get_index(
+        keymap: *mut XkbKeymap,
+        name: *const c_char,
+    ) -> u32;
+    fn xkb_keymap_key_get_syms_by_level(
+        keymap: *mut XkbKeymap,
+        key: u32,
+        layout: u32,
+        level: u32,
+        syms_out: *mut *const u32,
+    ) -> c_int;
+    fn xkb_state_new(keymap: *mut XkbKeymap) -> *mut XkbState;
+    fn xkb_state_unref(state: *mut XkbState) -> ();
+    fn xkb_state_update_mask(
+        state: *mut XkbState,
+        depressed_mods: u32,
+        latched_mods: u32,
+        locked_mods: u32,
+        depressed_layout: u32,
+        latched_layout: u32,
+        locked_layout: u32,
+    ) -> c_int;
+    fn xkb_state_key_get_layout(state: *mut XkbState, key: u32) -> u32;
+    fn xkb_state_key_get_utf8(
+        state: *mut XkbState,
+        key: u32,
+        buffer: *mut c_char,
+        size: usize,
+    ) -> c_int;
+});
+
+linker!(extern "C" LibC "libc.so.6" {
+    fn mmap(
+        addr: *mut c_void,
+        length: usize,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: isize,
+    ) -> *mut c_void;
+    fn munmap(addr: *mut c_void, length: usize) -> c_int;
+});
+
+/// The keymap, layout & modifier state of the keyboard.
+pub(super) struct Keymap {
+    // libxkbcommon (and libc for `mmap()`), if installed.  Without them, a US
+    // QWERTY layout is assumed.
+    xkb: Option<(XkbCommon, LibC)>,
+    context: *mut XkbContext,
+    keymap: *mut XkbKeymap,
+    state: *mut XkbState,
+    // Bits for each modifier in the masks.
+    shift: u32,
+    caps_lock: u32,
+    ctrl: u32,
+    alt: u32,
+    num_lock: u32,
+    // Latest modifier masks.
+    depressed: u32,
+    latched: u32,
+    locked: u32,
+}
+
+impl Keymap {
+    /// Load libxkbcommon, using the usual modifier bits until the compositor
+    /// sends a keymap.
+    pub(super) fn new() -> Self {
+        let xkb = match (XkbCommon::new(), LibC::new()) {
+            (Ok(xkb), Ok(libc)) => Some((xkb, libc)),
+            _ => None,
+        };
+        let context = match xkb {
+            Some((ref xkb, _)) => unsafe {
+                (xkb.xkb_context_new)(0 /*XKB_CONTEXT_NO_FLAGS*/)
+            },
+            None => null_mut(),
+        };
+
+        Keymap {
+            xkb,
+            context,
+            keymap: null_mut(),
+            state: null_mut(),
+            shift: 1 << 0,
+            caps_lock: 1 << 1,
+            ctrl: 1 << 2,
+            alt: 1 << 3,
+            num_lock: 1 << 4,
+            depressed: 0,
+            latched: 0,
+            locked: 0,
+        }
+    }
+
+    /// Load an XKB keymap (text format v1) from a file descriptor (which is
+    /// closed afterwards).
+    pub(super) fn set_keymap(&mut self, fd: c_int, size: usize) {
+        // Close the file descriptor when done.
+        let _file = unsafe { File::from_raw_fd(fd) };
+        let xkb = match self.xkb {
+            Some(ref xkb) if !self.context.is_null() => xkb,
+            _ => return,
+        };
+
+        let (keymap, state, bits) = unsafe {
+            let (xkb, libc) = (&xkb.0, &xkb.1);
+            let map = (libc.mmap)(
+                null_mut(),
+                size,
+                0x1, /*PROT_READ*/
+                0x2, /*MAP_PRIVATE*/
+                fd,
+                0,
+            );
+            if map as isize == -1 {
+                return;
+            }
+            let keymap = (xkb.xkb_keymap_new_from_string)(
+                self.context,
+                map.cast(),
+                1, /*XKB_KEYMAP_FORMAT_TEXT_V1*/
+                0, /*XKB_KEYMAP_COMPILE_NO_FLAGS*/
+            );
+            (libc.munmap)(map, size);
+            if keymap.is_null() {
+                return;
+            }
+            let state = (xkb.xkb_state_new)(keymap);
+            if state.is_null() {
+                (xkb.xkb_keymap_unref)(keymap);
+                return;
+            }
+
+            let bit = |name: &[u8]| {
+                let name = CStr::from_bytes_with_nul_unchecked(name);
+                match (xkb.xkb_keymap_mod_get_index)(keymap, name.as_ptr()) {
+                    u32::MAX /*XKB_MOD_INVALID*/ => 0,
+                    index => 1 << index,
+                }
+            };
+            let bits = [
+                bit(b"Shift\0"),
+                bit(b"Lock\0"),
+                bit(b"Control\0"),
+                bit(b"Mod1\0"),
+                bit(b"Mod2\0"),
+            ];
+            (keymap, state, bits)
+        };
+
+        self.unref();
+        self.keymap = keymap;
+        self.state = state;
+        let [shift, caps_lock, ctrl, alt, num_lock] = bits;
+        self.shift = shift;
+        self.caps_lock = caps_lock;
+        self.ctrl = ctrl;
+        self.alt = alt;
+        self.num_lock = num_lock;
+        self.depressed = 0;
+        self.latched = 0;
+        self.locked = 0;
+    }
+
+    /// Update from `wl_keyboard.modifiers`.
+    pub(super) fn update(
+        &mut self,
+        depressed: u32,
+        latched: u32,
+        locked: u32,
+        group: u32,
+    ) {
+        self.depressed = depressed;
+        self.latched = latched;
+        self.locked = locked;
+
+        if let Some((ref xkb, _)) = self.xkb {
+            if !self.state.is_null() {
+                unsafe {
+                    (xkb.xkb_state_update_mask)(
+                        self.state, depressed, latched, locked, 0, 0, group,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Modifiers that apply to input (held, latched or locked).
+    pub(super) fn mods(&self) -> Mod {
+        let active = self.depressed | self.latched | self.locked;
+        let mut mods = Mod::new();
+
+        if active & self.shift != 0 {
+            mods = mods.add_shift();
+        }
+        if active & self.ctrl != 0 {
+            mods = mods.add_ctrl();
+        }
+        if active & self.alt != 0 {
+            mods = mods.add_alt();
+        }
+        mods
+    }
+
+    /// Check if Caps Lock is on.
+    pub(super) fn caps_lock(&self) -> bool {
+        self.locked & self.caps_lock != 0
+    }
+
+    /// Check if Num Lock is on.
+    pub(super) fn num_lock(&self) -> bool {
+        self.locked & self.num_lock != 0
+    }
+
+    /// Get the key for an evdev key code in the current layout, ignoring
+    /// modifiers (so Shift+1 is still `Key::One`).  `None` if there's no
+    /// keymap or the key has no matching `Key`.
+    pub(super) fn key(&self, code: u32) -> Option<Key> {
+        let xkb = &self.xkb.as_ref()?.0;
+        if self.state.is_null() {
+            return None;
+        }
+        let code = code + 8;
+        let sym = unsafe {
+            let layout = (xkb.xkb_state_key_get_layout)(self.state, code);
+            let mut syms = std::ptr::null();
+            let count = (xkb.xkb_keymap_key_get_syms_by_level)(
+                self.keymap,
+                code,
+                layout,
+                0,
+                &mut syms,
+            );
+            if count < 1 {
+                return None;
+            }
+            *syms
+        };
+
+        keysym_key(sym)
+    }
+
+    /// Check if an evdev key code should repeat while held.  Without a
+    /// keymap, every key except the modifiers & locks repeats.
+    pub(super) fn repeats(&self, code: u32) -> bool {
+        match self.xkb {
+            Some((ref xkb, _)) if !self.keymap.is_null() => unsafe {
+                (xkb.xkb_keymap_key_repeats)(self.keymap, code + 8) != 0
+            },
+            _ => !matches!(
+                code,
+                29 | 42 | 54 | 56 | 58 | 69 | 97 | 100 | 125 | 126
+            ),
+        }
+    }
+
+    /// Get the text typed by an evdev key code, with the current modifiers
+    /// and layout.  Control characters are left out.
+    pub(super) fn text(&self, code: u32) -> String {
+        let xkb = match self.xkb {
+            Some((ref xkb, _)) if !self.state.is_null() => xkb,
+            _ => return String::new(),
+        };
+        let mut buffer = [0u8; 64];
+        let len = unsafe {
+            (xkb.xkb_state_key_get_utf8)(
+                self.state,
+                code + 8,
+                buffer.as_mut_ptr().cast(),
+                buffer.len(),
+            )
+        };
+        let len = (len.max(0) as usize).min(buffer.len() - 1);
+
+        String::from_utf8_lossy(&buffer[..len])
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect()
+    }
+
+    // Free the keymap & state.
+    fn unref(&mut self) {
+        if let Some((ref xkb, _)) = self.xkb {
+            unsafe {
+                if !self.state.is_null() {
+                    (xkb.xkb_state_unref)(self.state);
+                }
+                if !self.keymap.is_null() {
+                    (xkb.xkb_keymap_unref)(self.keymap);
+                }
+            }
+        }
+        self.state = null_mut();
+        self.keymap = null_mut();
+    }
+}
+
+impl Drop for Keymap {
+    fn drop(&mut self) {
+        self.unref();
+        if let Some((ref xkb, _)) = self.xkb {
+            if !self.context.is_null() {
+                unsafe { (xkb.xkb_context_unref)(self.context) };
+            }
+        }
+    }
+}
+
+// Get the `Key` for an XKB keysym.
+fn keysym_key(sym: u32) -> Option<Key> {
+    Some(match sym {
+        0x0061 | 0x0041 => Key::A,
+        0x0062 | 0x0042 => Key::B,
+        0x0063 | 0x0043 => Key::C,
+        0x0064 | 0x0044 => Key::D,
+        0x0065 | 0x0045 => Key::E,
+        0x0066 | 0x0046 => Key::F,
+        0x0067 | 0x0047 => Key::G,
+        0x0068 | 0x0048 => Key::H,
+        0x0069 | 0x0049 => Key::I,
+        0x006A | 0x004A => Key::J,
+        0x006B | 0x004B => Key::K,
+        0x006C | 0x004C => Key::L,
+        0x006D | 0x004D => Key::M,
+        0x006E | 0x004E => Key::N,
+        0x006F | 0x004F => Key::O,
+        0x0070 | 0x0050 => Key::P,
+        0x0071 | 0x0051 => Key::Q,
+        0x0072 | 0x0052 => Key::R,
+        0x0073 | 0x0053 => Key::S,
+        0x0074 | 0x0054 => Key::T,
+        0x0075 | 0x0055 => Key::U,
+        0x0076 | 0x0056 => Key::V,
+        0x0077 | 0x0057 => Key::W,
+        0x0078 | 0x0058 => Key::X,
+        0x0079 | 0x0059 => Key::Y,
+        0x007A | 0x005A => Key::Z,
+        0x0030 => Key::Zero,
+        0x0031 => Key::One,
+        0x0032 => Key::Two,
+        0x0033 => Key::Three,
+        0x0034 => Key::Four,
+        0x0035 => Key::Five,
+        0x0036 => Key::Six,
+        0x0037 => Key::Seven,
+        0x0038 => Key::Eight,
+        0x0039 => Key::Nine,
+        0x0020 => Key::Space,
+        0x0027 => Key::Apostrophe,
+        0x002C => Key::Comma,
+        0x002D => Key::Minus,
+        0x002E => Key::Period,
+        0x002F => Key::Slash,
+        0x003B => Key::Semicolon,
+        0x003D => Key::Equal,
+        0x005B => Key::BracketOpen,
+        0x005C => Key::Backslash,
+        0x005D => Key::BracketClose,
+        0x0060 => Key::Backtick,
+        0xFF08 => Key::Backspace,
+        0xFF09 | 0xFE20 /*ISO_Left_Tab*/ => Key::Tab,
+        0xFF0D | 0xFF8D /*KP_Enter*/ => Key::Enter,
+        0xFF13 => Key::Pause,
+        0xFF1B => Key::Back, // Escape
+        0xFF20 => Key::Compose, // Multi_key
+        0xFF50 => Key::Home,
+        0xFF51 => Key::Left,
+        0xFF52 => Key::Up,
+        0xFF53 => Key::Right,
+        0xFF54 => Key::Down,
+        0xFF55 => Key::PageUp,
+        0xFF56 => Key::PageDown,
+        0xFF57 => Key::End,
+        0xFF63 => Key::Insert,
+        0xFF67 => Key::Menu,
+        0xFFBE => Key::F1,
+        0xFFBF => Key::F2,
+        0xFFC0 => Key::F3,
+        0xFFC1 => Key::F4,
+        0xFFC2 => Key::F5,
+        0xFFC3 => Key::F6,
+        0xFFC4 => Key::F7,
+        0xFFC5 => Key::F8,
+        0xFFC6 => Key::F9,
+        0xFFC7 => Key::F10,
+        0xFFC8 => Key::F11,
+        0xFFC9 => Key::F12,
+        0xFFE1 => Key::LShift,
+        0xFFE2 => Key::RShift,
+        0xFFE3 | 0xFFEB /*Super_L*/ => Key::LCtrl,
+        0xFFE4 | 0xFFEC /*Super_R*/ => Key::RCtrl,
+        0xFFE5 => Key::CapsLock,
+        0xFFE9 => Key::LAlt,
+        0xFFEA | 0xFE03 /*ISO_Level3_Shift*/ => Key::RAlt,
+        0xFFFF => Key::Delete,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::keysym_key;
+    use human::Key;
+
+    #[test]
+    fn letters_ignore_case() {
+        assert!(matches!(keysym_key(0x0061), Some(Key::A)));
+        assert!(matches!(keysym_key(0x0041), Some(Key::A)));
+        assert!(matches!(keysym_key(0x007A), Some(Key::Z)));
+        assert!(matches!(keysym_key(0x005A), Some(Key::Z)));
+    }
+
+    #[test]
+    fn digits_and_punctuation() {
+        assert!(matches!(keysym_key(0x0030), Some(Key::Zero)));
+        assert!(matches!(keysym_key(0x0039), Some(Key::Nine)));
+        assert!(matches!(keysym_key(0x0020), Some(Key::Space)));
+        assert!(matches!(keysym_key(0x005B), Some(Key::BracketOpen)));
+        assert!(matches!(keysym_key(0x0060), Some(Key::Backtick)));
+    }
+
+    #[test]
+    fn function_and_editing_keys() {
+        assert!(matches!(keysym_key(0xFFBE), Some(Key::F1)));
+        assert!(matches!(keysym_key(0xFFC9), Some(Key::F12)));
+        assert!(matches!(keysym_key(0xFF1B), Some(Key::Back)));
+        assert!(matches!(keysym_key(0xFF08), Some(Key::Backspace)));
+        assert!(matches!(keysym_key(0xFFFF), Some(Key::Delete)));
+        assert!(matches!(keysym_key(0xFF51), Some(Key::Left)));
+    }
+
+    #[test]
+    fn keys_with_two_keysyms() {
+        assert!(matches!(keysym_key(0xFE20), Some(Key::Tab)));
+        assert!(matches!(keysym_key(0xFF8D), Some(Key::Enter)));
+        assert!(matches!(keysym_key(0xFFEB), Some(Key::LCtrl)));
+        assert!(matches!(keysym_key(0xFE03), Some(Key::RAlt)));
+    }
+
+    #[test]
+    fn modifiers() {
+        assert!(matches!(keysym_key(0xFFE1), Some(Key::LShift)));
+        assert!(matches!(keysym_key(0xFFE2), Some(Key::RShift)));
+        assert!(matches!(keysym_key(0xFFE3), Some(Key::LCtrl)));
+        assert!(matches!(keysym_key(0xFFE9), Some(Key::LAlt)));
+        assert!(matches!(keysym_key(0xFFE5), Some(Key::CapsLock)));
+    }
+
+    #[test]
+    fn unmapped_keysyms() {
+        assert!(keysym_key(0).is_none());
+        assert!(keysym_key(0x00E9).is_none()); // eacute
+        assert!(keysym_key(0x1008FF11).is_none()); // XF86AudioLowerVolume
+    }
+}
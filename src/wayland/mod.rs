@@ -8,19 +8,41 @@
 // At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
 // LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
 
+mod keymap;
+mod touch;
+
 use human::{Btn, Input, Key, Mod};
 
+use self::keymap::Keymap;
+use self::touch::Touches;
+use crate::WindowError;
+
 use dl_api::linker;
 
 use std::{
+    collections::VecDeque,
     convert::TryInto,
     ffi::{CStr, CString},
-    os::raw::{c_char, c_int, c_uint, c_void},
-    ptr::{null, null_mut, NonNull},
+    fs::File,
+    os::raw::{c_char, c_int, c_uint, c_ulong, c_void},
+    os::unix::io::FromRawFd,
+    ptr::{addr_of, null, null_mut, NonNull},
+    rc::Rc,
     str,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+extern "C" {
+    fn poll(fds: *mut PollFd, nfds: c_ulong, timeout: c_int) -> c_int;
+}
+
+#[repr(C)]
+struct PollFd {
+    fd: c_int,
+    events: i16,
+    revents: i16,
+}
+
 /* */
 
 static ZXDG_TOPLEVEL_V6_INTERFACE_NAME: &[u8] = b"zxdg_toplevel_v6\0";
@@ -271,160 +293,723 @@ static mut ZXDG_SHELL_V6_INTERFACE: WlInterface = WlInterface {
     .as_ptr(), // *wl_message
 };
 
-/* * From wayland-client-core.h  * */
-
-#[repr(transparent)]
-struct WlProxy(c_void);
+// Stable xdg-shell uses the same requests & events (with the same opcodes)
+// as zxdg_shell_v6, for everything up to version 2, so the tables, proxy
+// types and helper functions are shared; only the interface names differ.
 
-#[repr(transparent)]
-struct WlDisplay(c_void);
+static mut XDG_TOPLEVEL_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"xdg_toplevel\0".as_ptr().cast(),
+    // Interface version
+    version: 2,
+    // Number of methods (requests)
+    method_count: 14,
+    // Method (request) signatures
+    methods: addr_of!(ZXDG_TOPLEVEL_V6_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 2,
+    // Event signatures
+    events: addr_of!(ZXDG_TOPLEVEL_V6_INTERFACE_EVENTS).cast(),
+};
 
-/* * From wayland-util.h  * */
+static mut XDG_SURFACE_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"xdg_surface\0".as_ptr().cast(),
+    // Interface version
+    version: 2,
+    // Number of methods (requests)
+    method_count: 5,
+    // Method (request) signatures
+    methods: addr_of!(ZXDG_SURFACE_V6_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 1,
+    // Event signatures
+    events: unsafe { ZXDG_SURFACE_V6_INTERFACE.events },
+};
 
-#[repr(C)]
-struct WlArray {
-    size: usize,
-    alloc: usize,
-    data: *mut c_void,
-}
+static mut XDG_WM_BASE_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"xdg_wm_base\0".as_ptr().cast(),
+    // Interface version
+    version: 2,
+    // Number of methods (requests)
+    method_count: 4,
+    // Method (request) signatures
+    methods: addr_of!(ZXDG_SHELL_V6_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 1,
+    // Event signatures
+    events: unsafe { ZXDG_SHELL_V6_INTERFACE.events },
+};
 
-#[repr(C)]
-struct WlMessage {
-    // Message name
-    name: *const c_char,
-    // Message signature
-    signature: *const c_char,
-    // Object argument interfaces
-    wl_interface: *const *const WlInterface,
-}
+static mut ZXDG_DECORATION_MANAGER_V1_INTERFACE_METHODS: [WlMessage; 2] = [
+    WlMessage {
+        name: b"destroy\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"get_toplevel_decoration\0".as_ptr().cast(),
+        signature: b"no\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
 
-#[repr(C)]
-struct WlInterface {
+static mut ZXDG_DECORATION_MANAGER_V1_INTERFACE: WlInterface = WlInterface {
     // Interface name
-    name: *const c_char,
+    name: b"zxdg_decoration_manager_v1\0".as_ptr().cast(),
     // Interface version
-    version: c_int,
+    version: 1,
     // Number of methods (requests)
-    method_count: c_int,
+    method_count: 2,
     // Method (request) signatures
-    methods: *const WlMessage,
+    methods: addr_of!(ZXDG_DECORATION_MANAGER_V1_INTERFACE_METHODS).cast(),
     // Number of events
-    event_count: c_int,
+    event_count: 0,
     // Event signatures
-    events: *const WlMessage,
-}
+    events: std::ptr::null(),
+};
 
-/* * From wayland-client-protocol.h  * */
+static mut ZXDG_TOPLEVEL_DECORATION_V1_INTERFACE_METHODS: [WlMessage; 3] = [
+    WlMessage {
+        name: b"destroy\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"set_mode\0".as_ptr().cast(),
+        signature: b"u\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"unset_mode\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
 
-#[repr(transparent)]
-struct WlSurface(c_void);
-#[repr(transparent)]
-struct WlRegistry(c_void);
-#[repr(transparent)]
-struct WlCompositor(c_void);
-#[repr(transparent)]
-struct WlSeat(c_void);
-#[repr(transparent)]
-struct WlCallback(c_void);
-#[repr(transparent)]
-struct WlOutput(c_void);
-#[repr(transparent)]
-struct WlKeyboard(c_void);
-#[repr(transparent)]
-struct WlPointer(c_void);
-#[repr(transparent)]
-struct WlTouch(c_void);
+static mut ZXDG_TOPLEVEL_DECORATION_V1_INTERFACE_EVENTS: [WlMessage; 1] =
+    [WlMessage {
+        name: b"configure\0".as_ptr().cast(),
+        signature: b"u\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    }];
 
-#[repr(C)]
-#[derive(Copy, Clone)]
-enum WlSeatCapability {
-    Pointer = 1,
-    Keyboard = 2,
-    Touch = 4,
-}
+static mut ZXDG_TOPLEVEL_DECORATION_V1_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"zxdg_toplevel_decoration_v1\0".as_ptr().cast(),
+    // Interface version
+    version: 1,
+    // Number of methods (requests)
+    method_count: 3,
+    // Method (request) signatures
+    methods: addr_of!(ZXDG_TOPLEVEL_DECORATION_V1_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 1,
+    // Event signatures
+    events: addr_of!(ZXDG_TOPLEVEL_DECORATION_V1_INTERFACE_EVENTS).cast(),
+};
 
-#[repr(C)]
-struct WlRegistryListener {
-    global: Option<
-        extern "C" fn(
-            data: *mut c_void,
-            wl_registry: *mut WlRegistry,
-            name: u32,
-            interface: *const c_char,
-            version: u32,
-        ) -> (),
-    >,
-    global_remove: Option<
-        extern "C" fn(
-            data: *mut c_void,
-            wl_registry: *mut WlRegistry,
-            name: u32,
-        ),
-    >,
-}
+static mut ZWP_TEXT_INPUT_MANAGER_V3_INTERFACE_METHODS: [WlMessage; 2] = [
+    WlMessage {
+        name: b"destroy\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"get_text_input\0".as_ptr().cast(),
+        signature: b"no\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
 
-#[repr(C)]
-struct WlCallbackListener {
-    done: Option<
-        extern "C" fn(
-            data: *mut c_void,
-            callback: *mut WlCallback,
-            callback_data: u32,
-        ) -> (),
-    >,
-}
+static mut ZWP_TEXT_INPUT_MANAGER_V3_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"zwp_text_input_manager_v3\0".as_ptr().cast(),
+    // Interface version
+    version: 1,
+    // Number of methods (requests)
+    method_count: 2,
+    // Method (request) signatures
+    methods: addr_of!(ZWP_TEXT_INPUT_MANAGER_V3_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 0,
+    // Event signatures
+    events: std::ptr::null(),
+};
 
-#[repr(C)]
-struct WlOutputListener {
-    geometry: Option<
-        extern "C" fn(
-            data: *mut c_void,
-            output: *mut WlOutput,
-            x: i32,
-            y: i32,
-            physical_width: i32,
-            physical_height: i32,
-            subpixel: i32,
-            make: *const c_char,
-            model: *const c_char,
-            transform: i32,
-        ) -> (),
-    >,
-    mode: Option<
-        extern "C" fn(
-            data: *mut c_void,
-            output: *mut WlOutput,
-            flags: u32,
-            width: i32,
-            height: i32,
-            refresh: i32,
-        ) -> (),
-    >,
-    done: Option<extern "C" fn(data: *mut c_void, output: *mut WlOutput) -> ()>,
-    scale: Option<
-        extern "C" fn(
-            data: *mut c_void,
-            output: *mut WlOutput,
-            factor: i32,
-        ) -> (),
-    >,
-}
+static mut ZWP_TEXT_INPUT_V3_INTERFACE_METHODS: [WlMessage; 8] = [
+    WlMessage {
+        name: b"destroy\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"enable\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"disable\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"set_surrounding_text\0".as_ptr().cast(),
+        signature: b"sii\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"set_text_change_cause\0".as_ptr().cast(),
+        signature: b"u\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"set_content_type\0".as_ptr().cast(),
+        signature: b"uu\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"set_cursor_rectangle\0".as_ptr().cast(),
+        signature: b"iiii\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"commit\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
 
-#[repr(C)]
-struct WlSeatListener {
-    capabilities: Option<
-        extern "C" fn(
-            data: *mut c_void,
-            seat: *mut WlSeat,
-            capabilites: u32,
-        ) -> (),
-    >,
-    name: Option<
-        extern "C" fn(
-            data: *mut c_void,
-            seat: *mut WlSeat,
-            name: *const c_char,
-        ) -> (),
+// Object arguments (`wl_surface`s) aren't type checked.
+static mut ZWP_TEXT_INPUT_V3_TYPES: [*const WlInterface; 1] =
+    [std::ptr::null()];
+
+static mut ZWP_TEXT_INPUT_V3_INTERFACE_EVENTS: [WlMessage; 6] = [
+    WlMessage {
+        name: b"enter\0".as_ptr().cast(),
+        signature: b"o\0".as_ptr().cast(),
+        wl_interface: addr_of!(ZWP_TEXT_INPUT_V3_TYPES).cast(),
+    },
+    WlMessage {
+        name: b"leave\0".as_ptr().cast(),
+        signature: b"o\0".as_ptr().cast(),
+        wl_interface: addr_of!(ZWP_TEXT_INPUT_V3_TYPES).cast(),
+    },
+    WlMessage {
+        name: b"preedit_string\0".as_ptr().cast(),
+        signature: b"?sii\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"commit_string\0".as_ptr().cast(),
+        signature: b"?s\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"delete_surrounding_text\0".as_ptr().cast(),
+        signature: b"uu\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"done\0".as_ptr().cast(),
+        signature: b"u\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
+
+static mut ZWP_TEXT_INPUT_V3_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"zwp_text_input_v3\0".as_ptr().cast(),
+    // Interface version
+    version: 1,
+    // Number of methods (requests)
+    method_count: 8,
+    // Method (request) signatures
+    methods: addr_of!(ZWP_TEXT_INPUT_V3_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 6,
+    // Event signatures
+    events: addr_of!(ZWP_TEXT_INPUT_V3_INTERFACE_EVENTS).cast(),
+};
+
+static mut ZWP_POINTER_CONSTRAINTS_V1_INTERFACE_METHODS: [WlMessage; 3] = [
+    WlMessage {
+        name: b"destroy\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"lock_pointer\0".as_ptr().cast(),
+        signature: b"noo?ou\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"confine_pointer\0".as_ptr().cast(),
+        signature: b"noo?ou\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
+
+static mut ZWP_POINTER_CONSTRAINTS_V1_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"zwp_pointer_constraints_v1\0".as_ptr().cast(),
+    // Interface version
+    version: 1,
+    // Number of methods (requests)
+    method_count: 3,
+    // Method (request) signatures
+    methods: addr_of!(ZWP_POINTER_CONSTRAINTS_V1_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 0,
+    // Event signatures
+    events: std::ptr::null(),
+};
+
+static mut ZWP_LOCKED_POINTER_V1_INTERFACE_METHODS: [WlMessage; 3] = [
+    WlMessage {
+        name: b"destroy\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"set_cursor_position_hint\0".as_ptr().cast(),
+        signature: b"ff\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"set_region\0".as_ptr().cast(),
+        signature: b"?o\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
+
+static mut ZWP_LOCKED_POINTER_V1_INTERFACE_EVENTS: [WlMessage; 2] = [
+    WlMessage {
+        name: b"locked\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"unlocked\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
+
+static mut ZWP_LOCKED_POINTER_V1_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"zwp_locked_pointer_v1\0".as_ptr().cast(),
+    // Interface version
+    version: 1,
+    // Number of methods (requests)
+    method_count: 3,
+    // Method (request) signatures
+    methods: addr_of!(ZWP_LOCKED_POINTER_V1_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 2,
+    // Event signatures
+    events: addr_of!(ZWP_LOCKED_POINTER_V1_INTERFACE_EVENTS).cast(),
+};
+
+static mut ZWP_CONFINED_POINTER_V1_INTERFACE_METHODS: [WlMessage; 2] = [
+    WlMessage {
+        name: b"destroy\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"set_region\0".as_ptr().cast(),
+        signature: b"?o\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
+
+static mut ZWP_CONFINED_POINTER_V1_INTERFACE_EVENTS: [WlMessage; 2] = [
+    WlMessage {
+        name: b"confined\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"unconfined\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
+
+static mut ZWP_CONFINED_POINTER_V1_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"zwp_confined_pointer_v1\0".as_ptr().cast(),
+    // Interface version
+    version: 1,
+    // Number of methods (requests)
+    method_count: 2,
+    // Method (request) signatures
+    methods: addr_of!(ZWP_CONFINED_POINTER_V1_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 2,
+    // Event signatures
+    events: addr_of!(ZWP_CONFINED_POINTER_V1_INTERFACE_EVENTS).cast(),
+};
+
+static mut ZWP_RELATIVE_POINTER_MANAGER_V1_INTERFACE_METHODS: [WlMessage; 2] = [
+    WlMessage {
+        name: b"destroy\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"get_relative_pointer\0".as_ptr().cast(),
+        signature: b"no\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
+
+static mut ZWP_RELATIVE_POINTER_MANAGER_V1_INTERFACE: WlInterface =
+    WlInterface {
+        // Interface name
+        name: b"zwp_relative_pointer_manager_v1\0".as_ptr().cast(),
+        // Interface version
+        version: 1,
+        // Number of methods (requests)
+        method_count: 2,
+        // Method (request) signatures
+        methods: addr_of!(ZWP_RELATIVE_POINTER_MANAGER_V1_INTERFACE_METHODS)
+            .cast(),
+        // Number of events
+        event_count: 0,
+        // Event signatures
+        events: std::ptr::null(),
+    };
+
+static mut ZWP_RELATIVE_POINTER_V1_INTERFACE_METHODS: [WlMessage; 1] =
+    [WlMessage {
+        name: b"destroy\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    }];
+
+static mut ZWP_RELATIVE_POINTER_V1_INTERFACE_EVENTS: [WlMessage; 1] =
+    [WlMessage {
+        name: b"relative_motion\0".as_ptr().cast(),
+        signature: b"uuffff\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    }];
+
+static mut ZWP_RELATIVE_POINTER_V1_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"zwp_relative_pointer_v1\0".as_ptr().cast(),
+    // Interface version
+    version: 1,
+    // Number of methods (requests)
+    method_count: 1,
+    // Method (request) signatures
+    methods: addr_of!(ZWP_RELATIVE_POINTER_V1_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 1,
+    // Event signatures
+    events: addr_of!(ZWP_RELATIVE_POINTER_V1_INTERFACE_EVENTS).cast(),
+};
+
+static mut ZWP_POINTER_GESTURES_V1_INTERFACE_METHODS: [WlMessage; 4] = [
+    WlMessage {
+        name: b"get_swipe_gesture\0".as_ptr().cast(),
+        signature: b"no\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"get_pinch_gesture\0".as_ptr().cast(),
+        signature: b"no\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"release\0".as_ptr().cast(),
+        signature: b"2\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"get_hold_gesture\0".as_ptr().cast(),
+        signature: b"3no\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
+
+static mut ZWP_POINTER_GESTURES_V1_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"zwp_pointer_gestures_v1\0".as_ptr().cast(),
+    // Interface version
+    version: 3,
+    // Number of methods (requests)
+    method_count: 4,
+    // Method (request) signatures
+    methods: addr_of!(ZWP_POINTER_GESTURES_V1_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 0,
+    // Event signatures
+    events: std::ptr::null(),
+};
+
+// Requests of swipe, pinch & hold gestures.
+static mut ZWP_POINTER_GESTURE_V1_INTERFACE_METHODS: [WlMessage; 1] =
+    [WlMessage {
+        name: b"destroy\0".as_ptr().cast(),
+        signature: b"\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    }];
+
+// Object arguments (`wl_surface`s) aren't type checked.
+static mut ZWP_POINTER_GESTURE_V1_TYPES: [*const WlInterface; 4] =
+    [std::ptr::null(); 4];
+
+static mut ZWP_POINTER_GESTURE_SWIPE_V1_INTERFACE_EVENTS: [WlMessage; 3] = [
+    WlMessage {
+        name: b"begin\0".as_ptr().cast(),
+        signature: b"uuou\0".as_ptr().cast(),
+        wl_interface: addr_of!(ZWP_POINTER_GESTURE_V1_TYPES).cast(),
+    },
+    WlMessage {
+        name: b"update\0".as_ptr().cast(),
+        signature: b"uff\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"end\0".as_ptr().cast(),
+        signature: b"uui\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
+
+static mut ZWP_POINTER_GESTURE_SWIPE_V1_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"zwp_pointer_gesture_swipe_v1\0".as_ptr().cast(),
+    // Interface version
+    version: 2,
+    // Number of methods (requests)
+    method_count: 1,
+    // Method (request) signatures
+    methods: addr_of!(ZWP_POINTER_GESTURE_V1_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 3,
+    // Event signatures
+    events: addr_of!(ZWP_POINTER_GESTURE_SWIPE_V1_INTERFACE_EVENTS).cast(),
+};
+
+static mut ZWP_POINTER_GESTURE_PINCH_V1_INTERFACE_EVENTS: [WlMessage; 3] = [
+    WlMessage {
+        name: b"begin\0".as_ptr().cast(),
+        signature: b"uuou\0".as_ptr().cast(),
+        wl_interface: addr_of!(ZWP_POINTER_GESTURE_V1_TYPES).cast(),
+    },
+    WlMessage {
+        name: b"update\0".as_ptr().cast(),
+        signature: b"uffff\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+    WlMessage {
+        name: b"end\0".as_ptr().cast(),
+        signature: b"uui\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
+
+static mut ZWP_POINTER_GESTURE_PINCH_V1_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"zwp_pointer_gesture_pinch_v1\0".as_ptr().cast(),
+    // Interface version
+    version: 2,
+    // Number of methods (requests)
+    method_count: 1,
+    // Method (request) signatures
+    methods: addr_of!(ZWP_POINTER_GESTURE_V1_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 3,
+    // Event signatures
+    events: addr_of!(ZWP_POINTER_GESTURE_PINCH_V1_INTERFACE_EVENTS).cast(),
+};
+
+static mut ZWP_POINTER_GESTURE_HOLD_V1_INTERFACE_EVENTS: [WlMessage; 2] = [
+    WlMessage {
+        name: b"begin\0".as_ptr().cast(),
+        signature: b"3uuou\0".as_ptr().cast(),
+        wl_interface: addr_of!(ZWP_POINTER_GESTURE_V1_TYPES).cast(),
+    },
+    WlMessage {
+        name: b"end\0".as_ptr().cast(),
+        signature: b"3uui\0".as_ptr().cast(),
+        wl_interface: std::ptr::null(),
+    },
+];
+
+static mut ZWP_POINTER_GESTURE_HOLD_V1_INTERFACE: WlInterface = WlInterface {
+    // Interface name
+    name: b"zwp_pointer_gesture_hold_v1\0".as_ptr().cast(),
+    // Interface version
+    version: 3,
+    // Number of methods (requests)
+    method_count: 1,
+    // Method (request) signatures
+    methods: addr_of!(ZWP_POINTER_GESTURE_V1_INTERFACE_METHODS).cast(),
+    // Number of events
+    event_count: 2,
+    // Event signatures
+    events: addr_of!(ZWP_POINTER_GESTURE_HOLD_V1_INTERFACE_EVENTS).cast(),
+};
+
+/* * From wayland-client-core.h  * */
+
+#[repr(transparent)]
+struct WlProxy(c_void);
+
+#[repr(transparent)]
+struct WlDisplay(c_void);
+
+/* * From wayland-util.h  * */
+
+#[repr(C)]
+struct WlArray {
+    size: usize,
+    alloc: usize,
+    data: *mut c_void,
+}
+
+#[repr(C)]
+struct WlMessage {
+    // Message name
+    name: *const c_char,
+    // Message signature
+    signature: *const c_char,
+    // Object argument interfaces
+    wl_interface: *const *const WlInterface,
+}
+
+#[repr(C)]
+struct WlInterface {
+    // Interface name
+    name: *const c_char,
+    // Interface version
+    version: c_int,
+    // Number of methods (requests)
+    method_count: c_int,
+    // Method (request) signatures
+    methods: *const WlMessage,
+    // Number of events
+    event_count: c_int,
+    // Event signatures
+    events: *const WlMessage,
+}
+
+/* * From wayland-client-protocol.h  * */
+
+#[repr(transparent)]
+struct WlSurface(c_void);
+#[repr(transparent)]
+struct WlRegistry(c_void);
+#[repr(transparent)]
+struct WlCompositor(c_void);
+#[repr(transparent)]
+struct WlSeat(c_void);
+#[repr(transparent)]
+struct WlCallback(c_void);
+#[repr(transparent)]
+struct WlOutput(c_void);
+#[repr(transparent)]
+struct WlKeyboard(c_void);
+#[repr(transparent)]
+struct WlPointer(c_void);
+#[repr(transparent)]
+struct WlTouch(c_void);
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+enum WlSeatCapability {
+    Pointer = 1,
+    Keyboard = 2,
+    Touch = 4,
+}
+
+#[repr(C)]
+struct WlRegistryListener {
+    global: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            wl_registry: *mut WlRegistry,
+            name: u32,
+            interface: *const c_char,
+            version: u32,
+        ) -> (),
+    >,
+    global_remove: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            wl_registry: *mut WlRegistry,
+            name: u32,
+        ),
+    >,
+}
+
+#[repr(C)]
+struct WlCallbackListener {
+    done: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            callback: *mut WlCallback,
+            callback_data: u32,
+        ) -> (),
+    >,
+}
+
+#[repr(C)]
+struct WlOutputListener {
+    geometry: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            output: *mut WlOutput,
+            x: i32,
+            y: i32,
+            physical_width: i32,
+            physical_height: i32,
+            subpixel: i32,
+            make: *const c_char,
+            model: *const c_char,
+            transform: i32,
+        ) -> (),
+    >,
+    mode: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            output: *mut WlOutput,
+            flags: u32,
+            width: i32,
+            height: i32,
+            refresh: i32,
+        ) -> (),
+    >,
+    done: Option<extern "C" fn(data: *mut c_void, output: *mut WlOutput) -> ()>,
+    scale: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            output: *mut WlOutput,
+            factor: i32,
+        ) -> (),
+    >,
+}
+
+#[repr(C)]
+struct WlSeatListener {
+    capabilities: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            seat: *mut WlSeat,
+            capabilites: u32,
+        ) -> (),
+    >,
+    name: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            seat: *mut WlSeat,
+            name: *const c_char,
+        ) -> (),
     >,
 }
 
@@ -575,6 +1160,15 @@ struct WlPointerListener {
             discrete: i32,
         ) -> (),
     >,
+    // High-resolution discrete steps (1/120 of a step)
+    axis_value120: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            pointer: *mut WlPointer,
+            axis: u32,
+            value120: i32,
+        ),
+    >,
 }
 
 #[repr(C)]
@@ -675,36 +1269,242 @@ struct ZxdgSurfaceListener {
     configure: Option<
         extern "C" fn(
             data: *mut c_void,
-            surface: *mut ZxdgSurface,
-            serial: u32,
-        ) -> (),
+            surface: *mut ZxdgSurface,
+            serial: u32,
+        ) -> (),
+    >,
+}
+
+#[repr(C)]
+struct ZxdgToplevelListener {
+    configure: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            toplevel: *mut ZxdgToplevel,
+            width: i32,
+            height: i32,
+            states: *mut WlArray,
+        ) -> (),
+    >,
+    close: Option<
+        extern "C" fn(data: *mut c_void, toplevel: *mut ZxdgToplevel) -> (),
+    >,
+}
+
+#[repr(C)]
+struct ZxdgShellListener {
+    ping: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            xdg_shell: *mut ZxdgShell,
+            serial: u32,
+        ) -> (),
+    >,
+}
+
+/* * From xdg-decoration-unstable-v1 * */
+
+#[repr(transparent)]
+struct ZxdgDecorationManager(c_void);
+#[repr(transparent)]
+struct ZxdgToplevelDecoration(c_void);
+
+#[repr(transparent)]
+struct ZwpPointerConstraints(c_void);
+// A locked or confined pointer.
+#[repr(transparent)]
+struct ZwpPointerConstraint(c_void);
+#[repr(transparent)]
+struct ZwpRelativePointerManager(c_void);
+#[repr(transparent)]
+struct ZwpRelativePointer(c_void);
+
+// Listener of locked & confined pointers.
+#[repr(C)]
+struct ZwpPointerConstraintListener {
+    // Locked or confined
+    active: Option<
+        extern "C" fn(data: *mut c_void, constraint: *mut ZwpPointerConstraint),
+    >,
+    // Unlocked or unconfined
+    inactive: Option<
+        extern "C" fn(data: *mut c_void, constraint: *mut ZwpPointerConstraint),
+    >,
+}
+
+#[repr(C)]
+struct ZwpRelativePointerListener {
+    relative_motion: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            relative_pointer: *mut ZwpRelativePointer,
+            utime_hi: u32,
+            utime_lo: u32,
+            dx: i32,
+            dy: i32,
+            dx_unaccel: i32,
+            dy_unaccel: i32,
+        ),
+    >,
+}
+
+#[repr(transparent)]
+struct ZwpPointerGestures(c_void);
+// A swipe, pinch or hold gesture.
+#[repr(transparent)]
+struct ZwpPointerGesture(c_void);
+
+#[repr(C)]
+struct ZwpPointerGestureSwipeListener {
+    begin: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            gesture: *mut ZwpPointerGesture,
+            serial: u32,
+            time: u32,
+            surface: *mut WlSurface,
+            fingers: u32,
+        ),
+    >,
+    update: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            gesture: *mut ZwpPointerGesture,
+            time: u32,
+            dx: i32,
+            dy: i32,
+        ),
+    >,
+    end: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            gesture: *mut ZwpPointerGesture,
+            serial: u32,
+            time: u32,
+            cancelled: i32,
+        ),
+    >,
+}
+
+#[repr(C)]
+struct ZwpPointerGesturePinchListener {
+    begin: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            gesture: *mut ZwpPointerGesture,
+            serial: u32,
+            time: u32,
+            surface: *mut WlSurface,
+            fingers: u32,
+        ),
+    >,
+    update: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            gesture: *mut ZwpPointerGesture,
+            time: u32,
+            dx: i32,
+            dy: i32,
+            scale: i32,
+            rotation: i32,
+        ),
+    >,
+    end: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            gesture: *mut ZwpPointerGesture,
+            serial: u32,
+            time: u32,
+            cancelled: i32,
+        ),
+    >,
+}
+
+#[repr(C)]
+struct ZwpPointerGestureHoldListener {
+    begin: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            gesture: *mut ZwpPointerGesture,
+            serial: u32,
+            time: u32,
+            surface: *mut WlSurface,
+            fingers: u32,
+        ),
+    >,
+    end: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            gesture: *mut ZwpPointerGesture,
+            serial: u32,
+            time: u32,
+            cancelled: i32,
+        ),
+    >,
+}
+
+#[repr(transparent)]
+struct ZwpTextInputManager(c_void);
+#[repr(transparent)]
+struct ZwpTextInput(c_void);
+
+#[repr(C)]
+struct ZwpTextInputListener {
+    enter: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            text_input: *mut ZwpTextInput,
+            surface: *mut WlSurface,
+        ),
+    >,
+    leave: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            text_input: *mut ZwpTextInput,
+            surface: *mut WlSurface,
+        ),
+    >,
+    preedit_string: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            text_input: *mut ZwpTextInput,
+            text: *const c_char,
+            cursor_begin: i32,
+            cursor_end: i32,
+        ),
+    >,
+    commit_string: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            text_input: *mut ZwpTextInput,
+            text: *const c_char,
+        ),
     >,
-}
-
-#[repr(C)]
-struct ZxdgToplevelListener {
-    configure: Option<
+    delete_surrounding_text: Option<
         extern "C" fn(
             data: *mut c_void,
-            toplevel: *mut ZxdgToplevel,
-            width: i32,
-            height: i32,
-            states: *mut WlArray,
-        ) -> (),
+            text_input: *mut ZwpTextInput,
+            before_length: u32,
+            after_length: u32,
+        ),
     >,
-    close: Option<
-        extern "C" fn(data: *mut c_void, toplevel: *mut ZxdgToplevel) -> (),
+    done: Option<
+        extern "C" fn(
+            data: *mut c_void,
+            text_input: *mut ZwpTextInput,
+            serial: u32,
+        ),
     >,
 }
 
 #[repr(C)]
-struct ZxdgShellListener {
-    ping: Option<
+struct ZxdgToplevelDecorationListener {
+    configure: Option<
         extern "C" fn(
             data: *mut c_void,
-            xdg_shell: *mut ZxdgShell,
-            serial: u32,
-        ) -> (),
+            decoration: *mut ZxdgToplevelDecoration,
+            mode: u32,
+        ),
     >,
 }
 
@@ -736,8 +1536,8 @@ static TOUCH_LISTENER: WlTouchListener = WlTouchListener {
     motion: Some(touch_handle_motion),
     frame: Some(touch_handle_frame),
     cancel: Some(touch_handle_cancel),
-    shape: None,
-    orientation: None,
+    shape: Some(touch_handle_shape),
+    orientation: Some(touch_handle_orientation),
 };
 static KEYBOARD_LISTENER: WlKeyboardListener = WlKeyboardListener {
     keymap: Some(keyboard_handle_keymap),
@@ -745,7 +1545,7 @@ static KEYBOARD_LISTENER: WlKeyboardListener = WlKeyboardListener {
     leave: Some(keyboard_handle_leave),
     key: Some(keyboard_handle_key),
     modifiers: Some(keyboard_handle_modifiers),
-    repeat_info: None,
+    repeat_info: Some(keyboard_handle_repeat_info),
 };
 static POINTER_LISTENER: WlPointerListener = WlPointerListener {
     enter: Some(pointer_handle_enter),
@@ -753,10 +1553,11 @@ static POINTER_LISTENER: WlPointerListener = WlPointerListener {
     motion: Some(pointer_handle_motion),
     button: Some(pointer_handle_button),
     axis: Some(pointer_handle_axis),
-    frame: None,
-    axis_source: None,
-    axis_stop: None,
-    axis_discrete: None,
+    frame: Some(pointer_handle_frame),
+    axis_source: Some(pointer_handle_axis_source),
+    axis_stop: Some(pointer_handle_axis_stop),
+    axis_discrete: Some(pointer_handle_axis_discrete),
+    axis_value120: Some(pointer_handle_axis_value120),
 };
 static OUTPUT_LISTENER: WlOutputListener = WlOutputListener {
     geometry: Some(output_geometry),
@@ -766,7 +1567,7 @@ static OUTPUT_LISTENER: WlOutputListener = WlOutputListener {
 };
 static SEAT_LISTENER: WlSeatListener = WlSeatListener {
     capabilities: Some(seat_handle_capabilities),
-    name: None,
+    name: Some(seat_handle_name),
 };
 static REGISTRY_LISTENER: WlRegistryListener = WlRegistryListener {
     global: Some(registry_global),
@@ -782,6 +1583,44 @@ static XDG_TOPLEVEL_LISTENER: ZxdgToplevelListener = ZxdgToplevelListener {
 static XDG_SURFACE_LISTENER: ZxdgSurfaceListener = ZxdgSurfaceListener {
     configure: Some(surface_configure),
 };
+static DECORATION_LISTENER: ZxdgToplevelDecorationListener =
+    ZxdgToplevelDecorationListener {
+        configure: Some(decoration_configure),
+    };
+static CONSTRAINT_LISTENER: ZwpPointerConstraintListener =
+    ZwpPointerConstraintListener {
+        active: Some(constraint_active),
+        inactive: Some(constraint_inactive),
+    };
+static RELATIVE_POINTER_LISTENER: ZwpRelativePointerListener =
+    ZwpRelativePointerListener {
+        relative_motion: Some(relative_motion),
+    };
+static SWIPE_LISTENER: ZwpPointerGestureSwipeListener =
+    ZwpPointerGestureSwipeListener {
+        begin: Some(swipe_begin),
+        update: Some(swipe_update),
+        end: Some(gesture_end),
+    };
+static PINCH_LISTENER: ZwpPointerGesturePinchListener =
+    ZwpPointerGesturePinchListener {
+        begin: Some(pinch_begin),
+        update: Some(pinch_update),
+        end: Some(gesture_end),
+    };
+static HOLD_LISTENER: ZwpPointerGestureHoldListener =
+    ZwpPointerGestureHoldListener {
+        begin: Some(hold_begin),
+        end: Some(gesture_end),
+    };
+static TEXT_INPUT_LISTENER: ZwpTextInputListener = ZwpTextInputListener {
+    enter: Some(text_input_enter),
+    leave: Some(text_input_leave),
+    preedit_string: Some(text_input_preedit_string),
+    commit_string: Some(text_input_commit_string),
+    delete_surrounding_text: Some(text_input_delete_surrounding_text),
+    done: Some(text_input_done),
+};
 
 // Wrapper around Wayland Library
 linker!(extern "C" WaylandClient "libwayland-client.so.0" {
@@ -820,6 +1659,14 @@ linker!(extern "C" WaylandClient "libwayland-client.so.0" {
         data: *mut c_void,
     ) -> c_int;
     fn wl_display_dispatch(display: *mut WlDisplay) -> c_int;
+    fn wl_display_dispatch_pending(display: *mut WlDisplay) -> c_int;
+    fn wl_display_prepare_read(display: *mut WlDisplay) -> c_int;
+    fn wl_display_read_events(display: *mut WlDisplay) -> c_int;
+    fn wl_display_cancel_read(display: *mut WlDisplay) -> ();
+    fn wl_display_get_fd(display: *mut WlDisplay) -> c_int;
+    fn wl_display_roundtrip(display: *mut WlDisplay) -> c_int;
+    fn wl_display_flush(display: *mut WlDisplay) -> c_int;
+    fn wl_display_disconnect(display: *mut WlDisplay) -> ();
 });
 
 impl WaylandClient {
@@ -831,11 +1678,11 @@ impl WaylandClient {
     }
 
     // Inline Functions From include/wayland-client-protocol.h
-    /*#[inline(always)]
+    #[inline(always)]
     unsafe fn surface_destroy(&self, surface: *mut WlSurface) {
         (self.wl_proxy_marshal)(surface.cast(), 0 /*WL_SURFACE_DESTROY*/);
         (self.wl_proxy_destroy)(surface.cast());
-    }*/
+    }
     #[inline(always)]
     unsafe fn pointer_set_cursor(
         &self,
@@ -1069,6 +1916,342 @@ impl WaylandClient {
         .cast()
     }
     #[inline(always)]
+    unsafe fn xdg_wm_base_get_xdg_surface(
+        &self,
+        shell: *mut ZxdgShell,
+        surface: *mut WlSurface,
+    ) -> *mut ZxdgSurface {
+        (self.wl_proxy_marshal_constructor)(
+            shell.cast(),
+            2, /*XDG_WM_BASE_GET_XDG_SURFACE*/
+            addr_of!(XDG_SURFACE_INTERFACE),
+            NIL,
+            surface,
+        )
+        .cast()
+    }
+    #[inline(always)]
+    unsafe fn zxdg_decoration_manager_v1_get_toplevel_decoration(
+        &self,
+        manager: *mut ZxdgDecorationManager,
+        toplevel: *mut ZxdgToplevel,
+    ) -> *mut ZxdgToplevelDecoration {
+        (self.wl_proxy_marshal_constructor)(
+            manager.cast(),
+            1, /*ZXDG_DECORATION_MANAGER_V1_GET_TOPLEVEL_DECORATION*/
+            addr_of!(ZXDG_TOPLEVEL_DECORATION_V1_INTERFACE),
+            NIL,
+            toplevel,
+        )
+        .cast()
+    }
+    #[inline(always)]
+    unsafe fn zxdg_decoration_manager_v1_destroy(
+        &self,
+        manager: *mut ZxdgDecorationManager,
+    ) {
+        (self.wl_proxy_marshal)(
+            manager.cast(),
+            0, /*ZXDG_DECORATION_MANAGER_V1_DESTROY*/
+        );
+        (self.wl_proxy_destroy)(manager.cast());
+    }
+    #[inline(always)]
+    unsafe fn zxdg_toplevel_decoration_v1_add_listener(
+        &self,
+        decoration: *mut ZxdgToplevelDecoration,
+        listener: *const ZxdgToplevelDecorationListener,
+        data: *mut c_void,
+    ) -> c_int {
+        (self.wl_proxy_add_listener)(decoration.cast(), listener.cast(), data)
+    }
+    #[inline(always)]
+    unsafe fn zxdg_toplevel_decoration_v1_set_mode(
+        &self,
+        decoration: *mut ZxdgToplevelDecoration,
+        mode: crate::Decorations,
+    ) {
+        (self.wl_proxy_marshal)(
+            decoration.cast(),
+            1, /*ZXDG_TOPLEVEL_DECORATION_V1_SET_MODE*/
+            match mode {
+                crate::Decorations::Client => 1u32,
+                crate::Decorations::Server => 2u32,
+            },
+        );
+    }
+    #[inline(always)]
+    unsafe fn zxdg_toplevel_decoration_v1_destroy(
+        &self,
+        decoration: *mut ZxdgToplevelDecoration,
+    ) {
+        (self.wl_proxy_marshal)(
+            decoration.cast(),
+            0, /*ZXDG_TOPLEVEL_DECORATION_V1_DESTROY*/
+        );
+        (self.wl_proxy_destroy)(decoration.cast());
+    }
+    #[inline(always)]
+    unsafe fn zwp_pointer_constraints_v1_constrain(
+        &self,
+        constraints: *mut ZwpPointerConstraints,
+        confine: bool,
+        surface: *mut WlSurface,
+        pointer: *mut WlPointer,
+    ) -> *mut ZwpPointerConstraint {
+        let (opcode, interface) = if confine {
+            (
+                2, /*ZWP_POINTER_CONSTRAINTS_V1_CONFINE_POINTER*/
+                addr_of!(ZWP_CONFINED_POINTER_V1_INTERFACE),
+            )
+        } else {
+            (
+                1, /*ZWP_POINTER_CONSTRAINTS_V1_LOCK_POINTER*/
+                addr_of!(ZWP_LOCKED_POINTER_V1_INTERFACE),
+            )
+        };
+        (self.wl_proxy_marshal_constructor)(
+            constraints.cast(),
+            opcode,
+            interface,
+            NIL,
+            surface,
+            pointer,
+            NIL,
+            2 as c_uint, /*ZWP_POINTER_CONSTRAINTS_V1_LIFETIME_PERSISTENT*/
+        )
+        .cast()
+    }
+    #[inline(always)]
+    unsafe fn zwp_pointer_constraint_v1_add_listener(
+        &self,
+        constraint: *mut ZwpPointerConstraint,
+        listener: *const ZwpPointerConstraintListener,
+        data: *mut c_void,
+    ) -> c_int {
+        (self.wl_proxy_add_listener)(constraint.cast(), listener.cast(), data)
+    }
+    #[inline(always)]
+    unsafe fn zwp_locked_pointer_v1_set_cursor_position_hint(
+        &self,
+        locked_pointer: *mut ZwpPointerConstraint,
+        x: c_int,
+        y: c_int,
+    ) {
+        (self.wl_proxy_marshal)(
+            locked_pointer.cast(),
+            1, /*ZWP_LOCKED_POINTER_V1_SET_CURSOR_POSITION_HINT*/
+            x,
+            y,
+        );
+    }
+    #[inline(always)]
+    unsafe fn zwp_relative_pointer_manager_v1_get_relative_pointer(
+        &self,
+        manager: *mut ZwpRelativePointerManager,
+        pointer: *mut WlPointer,
+    ) -> *mut ZwpRelativePointer {
+        (self.wl_proxy_marshal_constructor)(
+            manager.cast(),
+            1, /*ZWP_RELATIVE_POINTER_MANAGER_V1_GET_RELATIVE_POINTER*/
+            addr_of!(ZWP_RELATIVE_POINTER_V1_INTERFACE),
+            NIL,
+            pointer,
+        )
+        .cast()
+    }
+    #[inline(always)]
+    unsafe fn zwp_relative_pointer_v1_add_listener(
+        &self,
+        relative_pointer: *mut ZwpRelativePointer,
+        listener: *const ZwpRelativePointerListener,
+        data: *mut c_void,
+    ) -> c_int {
+        (self.wl_proxy_add_listener)(
+            relative_pointer.cast(),
+            listener.cast(),
+            data,
+        )
+    }
+    // Destroy any of the pointer constraints & relative pointer objects
+    // (which all have destroy as their first request).
+    #[inline(always)]
+    unsafe fn zwp_pointer_v1_destroy(&self, proxy: *mut WlProxy) {
+        (self.wl_proxy_marshal)(proxy, 0 /*DESTROY*/);
+        (self.wl_proxy_destroy)(proxy);
+    }
+    #[inline(always)]
+    unsafe fn zwp_pointer_gestures_v1_get_gesture(
+        &self,
+        gestures: *mut ZwpPointerGestures,
+        opcode: u32,
+        interface: *const WlInterface,
+        pointer: *mut WlPointer,
+    ) -> *mut ZwpPointerGesture {
+        (self.wl_proxy_marshal_constructor)(
+            gestures.cast(),
+            opcode,
+            interface,
+            NIL,
+            pointer,
+        )
+        .cast()
+    }
+    #[inline(always)]
+    unsafe fn zwp_pointer_gestures_v1_destroy(
+        &self,
+        gestures: *mut ZwpPointerGestures,
+        version: u32,
+    ) {
+        // Version 1 has no destructor request.
+        if version >= 2 {
+            (self.wl_proxy_marshal)(
+                gestures.cast(),
+                2, /*ZWP_POINTER_GESTURES_V1_RELEASE*/
+            );
+        }
+        (self.wl_proxy_destroy)(gestures.cast());
+    }
+    #[inline(always)]
+    unsafe fn zwp_pointer_gesture_v1_add_listener(
+        &self,
+        gesture: *mut ZwpPointerGesture,
+        listener: *const c_void,
+        data: *mut c_void,
+    ) -> c_int {
+        (self.wl_proxy_add_listener)(gesture.cast(), listener.cast(), data)
+    }
+    #[inline(always)]
+    unsafe fn zwp_pointer_gesture_v1_destroy(
+        &self,
+        gesture: *mut ZwpPointerGesture,
+    ) {
+        (self.wl_proxy_marshal)(
+            gesture.cast(),
+            0, /*ZWP_POINTER_GESTURE_*_V1_DESTROY*/
+        );
+        (self.wl_proxy_destroy)(gesture.cast());
+    }
+    #[inline(always)]
+    unsafe fn zwp_text_input_manager_v3_get_text_input(
+        &self,
+        manager: *mut ZwpTextInputManager,
+        seat: *mut WlSeat,
+    ) -> *mut ZwpTextInput {
+        (self.wl_proxy_marshal_constructor)(
+            manager.cast(),
+            1, /*ZWP_TEXT_INPUT_MANAGER_V3_GET_TEXT_INPUT*/
+            addr_of!(ZWP_TEXT_INPUT_V3_INTERFACE),
+            NIL,
+            seat,
+        )
+        .cast()
+    }
+    #[inline(always)]
+    unsafe fn zwp_text_input_manager_v3_destroy(
+        &self,
+        manager: *mut ZwpTextInputManager,
+    ) {
+        (self.wl_proxy_marshal)(
+            manager.cast(),
+            0, /*ZWP_TEXT_INPUT_MANAGER_V3_DESTROY*/
+        );
+        (self.wl_proxy_destroy)(manager.cast());
+    }
+    #[inline(always)]
+    unsafe fn zwp_text_input_v3_add_listener(
+        &self,
+        text_input: *mut ZwpTextInput,
+        listener: *const ZwpTextInputListener,
+        data: *mut c_void,
+    ) -> c_int {
+        (self.wl_proxy_add_listener)(text_input.cast(), listener.cast(), data)
+    }
+    #[inline(always)]
+    unsafe fn zwp_text_input_v3_destroy(&self, text_input: *mut ZwpTextInput) {
+        (self.wl_proxy_marshal)(
+            text_input.cast(),
+            0, /*ZWP_TEXT_INPUT_V3_DESTROY*/
+        );
+        (self.wl_proxy_destroy)(text_input.cast());
+    }
+    #[inline(always)]
+    unsafe fn zwp_text_input_v3_enable(&self, text_input: *mut ZwpTextInput) {
+        (self.wl_proxy_marshal)(
+            text_input.cast(),
+            1, /*ZWP_TEXT_INPUT_V3_ENABLE*/
+        );
+    }
+    #[inline(always)]
+    unsafe fn zwp_text_input_v3_disable(&self, text_input: *mut ZwpTextInput) {
+        (self.wl_proxy_marshal)(
+            text_input.cast(),
+            2, /*ZWP_TEXT_INPUT_V3_DISABLE*/
+        );
+    }
+    #[inline(always)]
+    unsafe fn zwp_text_input_v3_set_surrounding_text(
+        &self,
+        text_input: *mut ZwpTextInput,
+        text: *const c_char,
+        cursor: c_int,
+        anchor: c_int,
+    ) {
+        (self.wl_proxy_marshal)(
+            text_input.cast(),
+            3, /*ZWP_TEXT_INPUT_V3_SET_SURROUNDING_TEXT*/
+            text,
+            cursor,
+            anchor,
+        );
+    }
+    #[inline(always)]
+    unsafe fn zwp_text_input_v3_set_cursor_rectangle(
+        &self,
+        text_input: *mut ZwpTextInput,
+        x: c_int,
+        y: c_int,
+        width: c_int,
+        height: c_int,
+    ) {
+        (self.wl_proxy_marshal)(
+            text_input.cast(),
+            6, /*ZWP_TEXT_INPUT_V3_SET_CURSOR_RECTANGLE*/
+            x,
+            y,
+            width,
+            height,
+        );
+    }
+    #[inline(always)]
+    unsafe fn zwp_text_input_v3_commit(&self, text_input: *mut ZwpTextInput) {
+        (self.wl_proxy_marshal)(
+            text_input.cast(),
+            7, /*ZWP_TEXT_INPUT_V3_COMMIT*/
+        );
+    }
+    #[inline(always)]
+    unsafe fn zxdg_shell_v6_destroy(&self, shell: *mut ZxdgShell) {
+        (self.wl_proxy_marshal)(shell.cast(), 0 /*ZXDG_SHELL_V6_DESTROY*/);
+        (self.wl_proxy_destroy)(shell.cast());
+    }
+    #[inline(always)]
+    unsafe fn zxdg_surface_v6_destroy(&self, surface: *mut ZxdgSurface) {
+        (self.wl_proxy_marshal)(
+            surface.cast(),
+            0, /*ZXDG_SURFACE_V6_DESTROY*/
+        );
+        (self.wl_proxy_destroy)(surface.cast());
+    }
+    #[inline(always)]
+    unsafe fn zxdg_toplevel_v6_destroy(&self, toplevel: *mut ZxdgToplevel) {
+        (self.wl_proxy_marshal)(
+            toplevel.cast(),
+            0, /*ZXDG_TOPLEVEL_V6_DESTROY*/
+        );
+        (self.wl_proxy_destroy)(toplevel.cast());
+    }
+    #[inline(always)]
     unsafe fn zxdg_surface_v6_get_toplevel(
         &self,
         surface: *mut ZxdgSurface,
@@ -1082,6 +2265,19 @@ impl WaylandClient {
         .cast()
     }
     #[inline(always)]
+    unsafe fn xdg_surface_get_toplevel(
+        &self,
+        surface: *mut ZxdgSurface,
+    ) -> *mut ZxdgToplevel {
+        (self.wl_proxy_marshal_constructor)(
+            surface.cast(),
+            1, /*XDG_SURFACE_GET_TOPLEVEL*/
+            addr_of!(XDG_TOPLEVEL_INTERFACE),
+            NIL,
+        )
+        .cast()
+    }
+    #[inline(always)]
     unsafe fn zxdg_surface_v6_add_listener(
         &self,
         surface: *mut ZxdgSurface,
@@ -1109,6 +2305,66 @@ impl WaylandClient {
         (self.wl_proxy_add_listener)(shell.cast(), listener.cast(), data)
     }
     #[inline(always)]
+    unsafe fn zxdg_toplevel_v6_show_window_menu(
+        &self,
+        toplevel: *mut ZxdgToplevel,
+        seat: *mut WlSeat,
+        serial: u32,
+        x: i32,
+        y: i32,
+    ) {
+        (self.wl_proxy_marshal)(
+            toplevel.cast(),
+            4, /*ZXDG_TOPLEVEL_V6_SHOW_WINDOW_MENU*/
+            seat,
+            serial,
+            x,
+            y,
+        );
+    }
+    #[inline(always)]
+    unsafe fn zxdg_toplevel_v6_move(
+        &self,
+        toplevel: *mut ZxdgToplevel,
+        seat: *mut WlSeat,
+        serial: u32,
+    ) {
+        (self.wl_proxy_marshal)(
+            toplevel.cast(),
+            5, /*ZXDG_TOPLEVEL_V6_MOVE*/
+            seat,
+            serial,
+        );
+    }
+    #[inline(always)]
+    unsafe fn zxdg_toplevel_v6_resize(
+        &self,
+        toplevel: *mut ZxdgToplevel,
+        seat: *mut WlSeat,
+        serial: u32,
+        edge: crate::ResizeEdge,
+    ) {
+        use crate::ResizeEdge::*;
+
+        let edges: u32 = match edge {
+            Top => 1,
+            Bottom => 2,
+            Left => 4,
+            TopLeft => 5,
+            BottomLeft => 6,
+            Right => 8,
+            TopRight => 9,
+            BottomRight => 10,
+        };
+        (self.wl_proxy_marshal)(
+            toplevel.cast(),
+            6, /*ZXDG_TOPLEVEL_V6_RESIZE*/
+            seat,
+            serial,
+            edges,
+        );
+    }
+    #[inline(always)]
     unsafe fn zxdg_toplevel_v6_set_title(
         &self,
         toplevel: *mut ZxdgToplevel,
@@ -1133,6 +2389,34 @@ impl WaylandClient {
         );
     }
     #[inline(always)]
+    unsafe fn zxdg_toplevel_v6_set_max_size(
+        &self,
+        toplevel: *mut ZxdgToplevel,
+        width: i32,
+        height: i32,
+    ) {
+        (self.wl_proxy_marshal)(
+            toplevel.cast(),
+            7, /*ZXDG_TOPLEVEL_V6_SET_MAX_SIZE*/
+            width,
+            height,
+        );
+    }
+    #[inline(always)]
+    unsafe fn zxdg_toplevel_v6_set_min_size(
+        &self,
+        toplevel: *mut ZxdgToplevel,
+        width: i32,
+        height: i32,
+    ) {
+        (self.wl_proxy_marshal)(
+            toplevel.cast(),
+            8, /*ZXDG_TOPLEVEL_V6_SET_MIN_SIZE*/
+            width,
+            height,
+        );
+    }
+    #[inline(always)]
     unsafe fn zxdg_toplevel_v6_set_maximized(
         &self,
         toplevel: *mut ZxdgToplevel,
@@ -1143,6 +2427,16 @@ impl WaylandClient {
         );
     }
     #[inline(always)]
+    unsafe fn zxdg_toplevel_v6_unset_maximized(
+        &self,
+        toplevel: *mut ZxdgToplevel,
+    ) {
+        (self.wl_proxy_marshal)(
+            toplevel.cast(),
+            10, /*ZXDG_TOPLEVEL_V6_UNSET_MAXIMIZED*/
+        );
+    }
+    #[inline(always)]
     unsafe fn zxdg_toplevel_v6_set_fullscreen(
         &self,
         toplevel: *mut ZxdgToplevel,
@@ -1164,6 +2458,16 @@ impl WaylandClient {
         );
     }
     #[inline(always)]
+    unsafe fn zxdg_toplevel_v6_set_minimized(
+        &self,
+        toplevel: *mut ZxdgToplevel,
+    ) {
+        (self.wl_proxy_marshal)(
+            toplevel.cast(),
+            13, /*ZXDG_TOPLEVEL_V6_SET_MINIMIZED*/
+        );
+    }
+    #[inline(always)]
     unsafe fn zxdg_surface_v6_ack_configure(
         &self,
         zxdg_surface_v6: *mut ZxdgSurface,
@@ -1184,271 +2488,1428 @@ impl WaylandClient {
         );
     }
 
-    #[inline(always)]
-    unsafe fn connect(&self) -> Option<NonNull<WlDisplay>> {
-        NonNull::new((self.wl_display_connect)(null()))
+    #[inline(always)]
+    unsafe fn connect(&self) -> Option<NonNull<WlDisplay>> {
+        NonNull::new((self.wl_display_connect)(null()))
+    }
+}
+
+linker!(extern "C" WaylandEGL "libwayland-egl.so.1" {
+    fn wl_egl_window_create(
+        surface: *mut WlSurface,
+        width: c_int,
+        height: c_int,
+    ) -> *mut WlEglWindow;
+    fn wl_egl_window_resize(
+        egl_window: *mut WlEglWindow,
+        width: c_int,
+        height: c_int,
+        dx: c_int,
+        dy: c_int,
+    ) -> ();
+    fn wl_egl_window_destroy(egl_window: *mut WlEglWindow) -> ();
+});
+
+linker!(extern "C" WaylandCursor "libwayland-cursor.so.0" {
+    fn wl_cursor_image_get_buffer(image: *mut WlCursorImage) -> *mut WlBuffer;
+    fn wl_cursor_theme_destroy(theme: *mut WlCursorTheme) -> ();
+    fn wl_cursor_theme_load(
+        name: *const c_char,
+        size: c_int,
+        shm: *mut WlShm,
+    ) -> *mut WlCursorTheme;
+    fn wl_cursor_theme_get_cursor(
+        theme: *mut WlCursorTheme,
+        name: *const c_char,
+    ) -> *mut WlCursor;
+});
+
+// Shared connection to the compositor, owned by `WaylandApp`.
+struct Connection {
+    // Shared Objects
+    client: WaylandClient,
+    egl: WaylandEGL,
+    cursor: WaylandCursor,
+
+    // Client
+    display: NonNull<WlDisplay>,
+    registry: *mut WlRegistry,
+    compositor: *mut WlCompositor,
+    seat: *mut WlSeat,
+    pointer: *mut WlPointer,
+    keyboard: *mut WlKeyboard,
+    touch: *mut WlTouch,
+    outputs: Vec<*mut WlOutput>,
+    shell: *mut ZxdgShell,
+    // If `shell` is stable xdg_wm_base rather than zxdg_shell_v6.
+    stable: bool,
+    decoration_manager: *mut ZxdgDecorationManager,
+    refresh_rate: Duration,
+
+    // Cursor
+    cursor_surface: *mut WlSurface,
+    default_cursor: *mut WlCursor,
+    cursor_theme: *mut WlCursorTheme,
+    shm: *mut WlShm,
+
+    // Open windows, and which of them have pointer & keyboard focus.
+    windows: Vec<*mut Wayland>,
+    pointer_focus: *mut Wayland,
+    keyboard_focus: *mut Wayland,
+    // Serial of the latest button press or touch, for move, resize & menus.
+    serial: u32,
+    // Latest pointer position on the focused surface, in pixels.
+    pointer_position: (c_int, c_int),
+    // Keyboard layout & modifiers, applied to all keyboard & pointer input.
+    keymap: Keymap,
+    // Key repeat settings (keys per second, 0 for no repeat, & milliseconds
+    // before the first repeat), and the held key with its next repeat time.
+    repeat_rate: i32,
+    repeat_delay: i32,
+    repeat: Option<(u32, Instant)>,
+    // Keys held down while the keyboard focus has been on a window.
+    held_keys: Vec<u32>,
+
+    // Touch points that are down.
+    touches: Touches<*mut Wayland>,
+
+    // Pointer locking & confining, and relative motion (for the pointer)
+    // with the motion of the current pointer frame.
+    pointer_constraints: *mut ZwpPointerConstraints,
+    relative_pointer_manager: *mut ZwpRelativePointerManager,
+    relative_pointer: *mut ZwpRelativePointer,
+    relative_motion: Option<crate::RelativeMotion>,
+
+    // Touchpad gestures (for the pointer), with the window and state of the
+    // current gesture.
+    gestures: *mut ZwpPointerGestures,
+    gestures_version: u32,
+    swipe: *mut ZwpPointerGesture,
+    pinch: *mut ZwpPointerGesture,
+    hold: *mut ZwpPointerGesture,
+    gesture_focus: *mut Wayland,
+    gesture: Option<crate::Gesture>,
+
+    // Input method support (for the seat), the window it's sending text to,
+    // and text waiting for `done`.
+    text_input_manager: *mut ZwpTextInputManager,
+    text_input: *mut ZwpTextInput,
+    text_input_focus: *mut Wayland,
+    text_input_enabled: bool,
+    preedit: Option<(String, Option<(usize, usize)>)>,
+    commit: Option<String>,
+    delete: (u32, u32),
+    // Preedit text last sent to the focused window.
+    current_preedit: (String, Option<(usize, usize)>),
+
+    // Input queues of the windows, and input received since the last
+    // `flush_input()`.
+    app_input: crate::ffi::AppInput,
+    input_queue: Vec<(crate::WindowId, Input)>,
+    // Whether the compositor groups pointer events with frames (otherwise
+    // they're grouped by `flush_input()`), and the pointer input & latest
+    // motion of the current frame.
+    pointer_frames: bool,
+    pointer_pending: Vec<Input>,
+    pointer_motion: Option<crate::PointerMotion>,
+    scroll: Option<crate::Scroll>,
+}
+
+impl Connection {
+    // Find the window that owns a surface.
+    fn window(&self, surface: *mut WlSurface) -> *mut Wayland {
+        for window in self.windows.iter().cloned() {
+            if unsafe { (*window).surface } == surface {
+                return window;
+            }
+        }
+        null_mut()
+    }
+
+    // Queue input for the window that has pointer focus (held until the end
+    // of the pointer frame).
+    fn pointer_input(&mut self, input: Input) {
+        if !self.pointer_focus.is_null() {
+            self.pointer_pending.push(input);
+        }
+    }
+
+    // Queue pointer input received since the last pointer frame, all at
+    // once.
+    fn pointer_frame(&mut self) {
+        let pending = std::mem::take(&mut self.pointer_pending);
+        let motion = self.pointer_motion.take();
+        let scroll = self.scroll.take();
+        let relative_motion = self.relative_motion.take();
+        if self.pointer_focus.is_null() {
+            return;
+        }
+        let wayland = unsafe { &mut *self.pointer_focus };
+
+        let id = wayland.id;
+        self.input_queue
+            .extend(pending.into_iter().map(|input| (id, input)));
+        if let Some(motion) = motion {
+            wayland
+                .events
+                .push_back(crate::WindowEvent::PointerMoved(motion));
+        }
+        if let Some(scroll) = scroll {
+            wayland.events.push_back(crate::WindowEvent::Scroll(scroll));
+        }
+        if let Some(motion) = relative_motion {
+            wayland
+                .events
+                .push_back(crate::WindowEvent::RelativeMotion(motion));
+        }
+    }
+
+    // Get the scrolling of the current pointer frame.
+    fn scroll(&mut self) -> &mut crate::Scroll {
+        self.scroll.get_or_insert(crate::Scroll {
+            source: crate::ScrollSource::Unknown,
+            x: 0.0,
+            y: 0.0,
+            steps_x: 0.0,
+            steps_y: 0.0,
+            stop_x: false,
+            stop_y: false,
+            time: 0,
+        })
+    }
+
+    // Move received input to the queues of the windows it was sent to, in
+    // order, and wake up input listeners.
+    fn flush_input(&mut self) {
+        if !self.pointer_frames {
+            self.pointer_frame();
+        }
+        if self.input_queue.is_empty() {
+            return;
+        }
+        for (id, input) in std::mem::take(&mut self.input_queue) {
+            let window =
+                self.windows.iter().find(|w| unsafe { (***w).id } == id);
+            if let Some(window) = window {
+                unsafe { (**window).input.push(id, input) };
+            }
+        }
+        crate::ffi::wake_listeners();
+    }
+
+    // Queue input for the window that has keyboard focus.
+    fn keyboard_input(&mut self, input: Input) {
+        if !self.keyboard_focus.is_null() {
+            let id = unsafe { (*self.keyboard_focus).id };
+            self.input_queue.push((id, input));
+        }
+    }
+
+    // Queue the input for a key press (or repeat) or release.
+    fn key_input(&mut self, key: u32, held: bool) {
+        self.key_state(key, held);
+        if held {
+            for c in self.keymap.text(key).chars() {
+                self.keyboard_input(Input::Text(c));
+            }
+        }
+    }
+
+    // Queue a key press or release (without text), keeping track of which
+    // keys are held.
+    fn key_state(&mut self, key: u32, held: bool) {
+        let mods = self.keymap.mods();
+
+        if held && !self.held_keys.contains(&key) {
+            self.held_keys.push(key);
+        } else if !held {
+            self.held_keys.retain(|k| *k != key);
+        }
+        // Use the layout's key if it has one, otherwise the US QWERTY key in
+        // the same place.
+        let input = match self.keymap.key(key) {
+            Some(k) => Some(Input::Key(mods, k, held)),
+            None => evdev_key(key, mods, held),
+        };
+        if let Some(input) = input {
+            self.keyboard_input(input);
+        }
+    }
+
+    // Repeat the held key if it's time to.
+    fn key_repeat(&mut self) {
+        let (key, next) = match self.repeat {
+            Some(repeat) if self.repeat_rate > 0 => repeat,
+            _ => return,
+        };
+        let now = Instant::now();
+        if now < next {
+            return;
+        }
+        let interval = Duration::from_secs(1) / self.repeat_rate as u32;
+
+        self.key_input(key, true);
+        // Skip repeats missed while the app was busy.
+        let mut next = next + interval;
+        if next < now {
+            next = now + interval;
+        }
+        self.repeat = Some((key, next));
+    }
+
+    // Dispatch events, waiting no longer than the next key repeat.
+    fn dispatch(&mut self) -> c_int {
+        let display = self.display.as_ptr();
+        let client = &self.client;
+        let timeout = match self.repeat {
+            Some((_, next)) if self.repeat_rate > 0 => {
+                next.saturating_duration_since(Instant::now())
+            }
+            _ => return unsafe { (client.wl_display_dispatch)(display) },
+        };
+
+        unsafe {
+            // Events already read go first.
+            if (client.wl_display_prepare_read)(display) != 0 {
+                return (client.wl_display_dispatch_pending)(display);
+            }
+            (client.wl_display_flush)(display);
+            let mut fd = PollFd {
+                fd: (client.wl_display_get_fd)(display),
+                events: 1, /*POLLIN*/
+                revents: 0,
+            };
+            // Round up, so the repeat is due when this returns.
+            let millis = timeout.as_micros().div_ceil(1000);
+            let millis = millis.try_into().unwrap_or(c_int::MAX);
+            if poll(&mut fd, 1, millis) > 0 {
+                if (client.wl_display_read_events)(display) == -1 {
+                    return -1;
+                }
+            } else {
+                (client.wl_display_cancel_read)(display);
+            }
+            (client.wl_display_dispatch_pending)(display)
+        }
+    }
+
+    // Queue input for a window from a touch point change.
+    fn touch_input(&mut self, wayland: *mut Wayland, input: Vec<Input>) {
+        let window = unsafe { (*wayland).id };
+        self.input_queue
+            .extend(input.into_iter().map(|input| (window, input)));
+    }
+
+    // Get relative motion for the pointer once both it and the manager are
+    // bound.
+    fn bind_relative_pointer(&mut self) {
+        if self.relative_pointer_manager.is_null()
+            || self.pointer.is_null()
+            || !self.relative_pointer.is_null()
+        {
+            return;
+        }
+        let data: *mut Connection = self;
+        unsafe {
+            self.relative_pointer = self
+                .client
+                .zwp_relative_pointer_manager_v1_get_relative_pointer(
+                    self.relative_pointer_manager,
+                    self.pointer,
+                );
+            self.client.zwp_relative_pointer_v1_add_listener(
+                self.relative_pointer,
+                &RELATIVE_POINTER_LISTENER,
+                data.cast(),
+            );
+        }
+    }
+
+    // Destroy the relative pointer (before the pointer).
+    fn unbind_relative_pointer(&mut self) {
+        if !self.relative_pointer.is_null() {
+            unsafe {
+                self.client
+                    .zwp_pointer_v1_destroy(self.relative_pointer.cast());
+            }
+            self.relative_pointer = null_mut();
+        }
+        self.relative_motion = None;
+    }
+
+    // Get gestures for the pointer once both it and the manager are bound.
+    fn bind_gestures(&mut self) {
+        if self.gestures.is_null()
+            || self.pointer.is_null()
+            || !self.swipe.is_null()
+        {
+            return;
+        }
+        let data: *mut Connection = self;
+        let client = &self.client;
+        unsafe {
+            self.swipe = client.zwp_pointer_gestures_v1_get_gesture(
+                self.gestures,
+                0, /*ZWP_POINTER_GESTURES_V1_GET_SWIPE_GESTURE*/
+                addr_of!(ZWP_POINTER_GESTURE_SWIPE_V1_INTERFACE),
+                self.pointer,
+            );
+            client.zwp_pointer_gesture_v1_add_listener(
+                self.swipe,
+                addr_of!(SWIPE_LISTENER).cast(),
+                data.cast(),
+            );
+            self.pinch = client.zwp_pointer_gestures_v1_get_gesture(
+                self.gestures,
+                1, /*ZWP_POINTER_GESTURES_V1_GET_PINCH_GESTURE*/
+                addr_of!(ZWP_POINTER_GESTURE_PINCH_V1_INTERFACE),
+                self.pointer,
+            );
+            client.zwp_pointer_gesture_v1_add_listener(
+                self.pinch,
+                addr_of!(PINCH_LISTENER).cast(),
+                data.cast(),
+            );
+            if self.gestures_version >= 3 {
+                self.hold = client.zwp_pointer_gestures_v1_get_gesture(
+                    self.gestures,
+                    3, /*ZWP_POINTER_GESTURES_V1_GET_HOLD_GESTURE*/
+                    addr_of!(ZWP_POINTER_GESTURE_HOLD_V1_INTERFACE),
+                    self.pointer,
+                );
+                client.zwp_pointer_gesture_v1_add_listener(
+                    self.hold,
+                    addr_of!(HOLD_LISTENER).cast(),
+                    data.cast(),
+                );
+            }
+        }
+    }
+
+    // Destroy the gestures of the pointer (before the pointer).
+    fn unbind_gestures(&mut self) {
+        for gesture in [&mut self.swipe, &mut self.pinch, &mut self.hold] {
+            if !gesture.is_null() {
+                unsafe { self.client.zwp_pointer_gesture_v1_destroy(*gesture) };
+                *gesture = null_mut();
+            }
+        }
+        self.gesture_focus = null_mut();
+        self.gesture = None;
+    }
+
+    // Send a gesture begin, update or end to the window it's on, with the
+    // pinch scale relative to the previous update.
+    fn gesture_event(&mut self, gesture: crate::Gesture, scale_change: f32) {
+        if self.gesture_focus.is_null() {
+            return;
+        }
+        let wayland = unsafe { &mut *self.gesture_focus };
+
+        wayland
+            .events
+            .push_back(crate::WindowEvent::Gesture(gesture));
+        if gesture.kind != crate::GestureKind::Pinch {
+            return;
+        }
+        let id = wayland.id;
+        match gesture.phase {
+            crate::GesturePhase::Begin => {
+                self.input_queue.push((id, Input::Pinch(true)));
+            }
+            crate::GesturePhase::Update => {
+                if scale_change != 1.0 {
+                    self.input_queue.push((id, Input::PinchW(scale_change)));
+                    self.input_queue.push((id, Input::PinchH(scale_change)));
+                }
+                if gesture.rotation != 0.0 {
+                    self.input_queue
+                        .push((id, Input::PinchZ(gesture.rotation)));
+                }
+            }
+            crate::GesturePhase::End | crate::GesturePhase::Cancel => {
+                self.input_queue.push((id, Input::Pinch(false)));
+            }
+        }
+    }
+
+    // Get text input for the seat once both it and the manager are bound.
+    fn bind_text_input(&mut self) {
+        if self.text_input_manager.is_null()
+            || self.seat.is_null()
+            || !self.text_input.is_null()
+        {
+            return;
+        }
+        let data: *mut Connection = self;
+        unsafe {
+            self.text_input =
+                self.client.zwp_text_input_manager_v3_get_text_input(
+                    self.text_input_manager,
+                    self.seat,
+                );
+            self.client.zwp_text_input_v3_add_listener(
+                self.text_input,
+                &TEXT_INPUT_LISTENER,
+                data.cast(),
+            );
+        }
+    }
+
+    // Remove the preedit text from the window with text input focus.
+    fn clear_preedit(&mut self) {
+        let cleared = (String::new(), None);
+        if self.current_preedit == cleared || self.text_input_focus.is_null() {
+            return;
+        }
+        self.current_preedit = cleared;
+        unsafe {
+            (*self.text_input_focus).events.push_back(
+                crate::WindowEvent::Preedit {
+                    text: String::new(),
+                    cursor: None,
+                },
+            );
+        }
+    }
+
+    // Send a window's text input state to the input method, if the window
+    // has text input focus.
+    fn text_input_update(&mut self, window: *mut Wayland) {
+        if self.text_input.is_null()
+            || window.is_null()
+            || self.text_input_focus != window
+        {
+            return;
+        }
+        let window = unsafe { &*window };
+
+        unsafe {
+            if window.text_input {
+                if !self.text_input_enabled {
+                    self.client.zwp_text_input_v3_enable(self.text_input);
+                    self.text_input_enabled = true;
+                }
+                if let Some((ref text, cursor, anchor)) =
+                    window.surrounding_text
+                {
+                    self.client.zwp_text_input_v3_set_surrounding_text(
+                        self.text_input,
+                        text.as_ptr(),
+                        cursor,
+                        anchor,
+                    );
+                }
+                let (x, y, width, height) = window.text_cursor;
+                self.client.zwp_text_input_v3_set_cursor_rectangle(
+                    self.text_input,
+                    x,
+                    y,
+                    width,
+                    height,
+                );
+            } else if self.text_input_enabled {
+                self.client.zwp_text_input_v3_disable(self.text_input);
+                self.text_input_enabled = false;
+                self.clear_preedit();
+            } else {
+                return;
+            }
+            self.client.zwp_text_input_v3_commit(self.text_input);
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        unsafe {
+            // Cursor
+            if !self.cursor_surface.is_null() {
+                self.client.surface_destroy(self.cursor_surface);
+            }
+            if !self.cursor_theme.is_null() {
+                (self.cursor.wl_cursor_theme_destroy)(self.cursor_theme);
+            }
+
+            // Input devices
+            self.unbind_relative_pointer();
+            self.unbind_gestures();
+            if !self.text_input.is_null() {
+                self.client.zwp_text_input_v3_destroy(self.text_input);
+            }
+            let devices: [*mut WlProxy; 4] = [
+                self.pointer.cast(),
+                self.keyboard.cast(),
+                self.touch.cast(),
+                self.seat.cast(),
+            ];
+            for proxy in devices {
+                if !proxy.is_null() {
+                    (self.client.wl_proxy_destroy)(proxy);
+                }
+            }
+
+            // Globals
+            for output in self.outputs.drain(..) {
+                (self.client.wl_proxy_destroy)(output.cast());
+            }
+            if !self.decoration_manager.is_null() {
+                self.client.zxdg_decoration_manager_v1_destroy(
+                    self.decoration_manager,
+                );
+            }
+            if !self.pointer_constraints.is_null() {
+                self.client
+                    .zwp_pointer_v1_destroy(self.pointer_constraints.cast());
+            }
+            if !self.relative_pointer_manager.is_null() {
+                self.client.zwp_pointer_v1_destroy(
+                    self.relative_pointer_manager.cast(),
+                );
+            }
+            if !self.gestures.is_null() {
+                self.client.zwp_pointer_gestures_v1_destroy(
+                    self.gestures,
+                    self.gestures_version,
+                );
+            }
+            if !self.text_input_manager.is_null() {
+                self.client
+                    .zwp_text_input_manager_v3_destroy(self.text_input_manager);
+            }
+            if !self.shell.is_null() {
+                self.client.zxdg_shell_v6_destroy(self.shell);
+            }
+            let globals: [*mut WlProxy; 3] = [
+                self.shm.cast(),
+                self.compositor.cast(),
+                self.registry.cast(),
+            ];
+            for proxy in globals {
+                if !proxy.is_null() {
+                    (self.client.wl_proxy_destroy)(proxy);
+                }
+            }
+
+            // Connection
+            (self.client.wl_display_flush)(self.display.as_ptr());
+            (self.client.wl_display_disconnect)(self.display.as_ptr());
+        }
+    }
+}
+
+// Wrapper around Wayland Libraries, shared between windows.
+pub(super) struct WaylandApp {
+    // Boxed so listeners can keep a stable pointer to it.
+    connection: *mut Connection,
+}
+
+impl WaylandApp {
+    pub(super) fn new() -> Result<Rc<Self>, WindowError> {
+        let client = WaylandClient::new().map_err(|_| {
+            WindowError::MissingLibrary("libwayland-client.so.0")
+        })?;
+        let egl = WaylandEGL::new()
+            .map_err(|_| WindowError::MissingLibrary("libwayland-egl.so.1"))?;
+        let cursor = WaylandCursor::new().map_err(|_| {
+            WindowError::MissingLibrary("libwayland-cursor.so.0")
+        })?;
+
+        // Needed for ZXDG extensions.
+        client.init();
+
+        unsafe {
+            // Connect to compositor.
+            let display = client.connect().ok_or(WindowError::NoConnection)?;
+            let registry = client.display_get_registry(display.as_ptr());
+            let app = Rc::new(WaylandApp {
+                connection: Box::into_raw(Box::new(Connection {
+                    client,
+                    egl,
+                    cursor,
+                    display,
+                    registry,
+                    compositor: null_mut(),
+                    seat: null_mut(),
+                    pointer: null_mut(),
+                    keyboard: null_mut(),
+                    touch: null_mut(),
+                    outputs: Vec::new(),
+                    shell: null_mut(),
+                    stable: false,
+                    decoration_manager: null_mut(),
+                    refresh_rate: Duration::new(0, 0),
+
+                    cursor_surface: null_mut(),
+                    default_cursor: null_mut(),
+                    cursor_theme: null_mut(),
+                    shm: null_mut(),
+
+                    windows: Vec::new(),
+                    pointer_focus: null_mut(),
+                    keyboard_focus: null_mut(),
+                    serial: 0,
+                    pointer_position: (0, 0),
+                    keymap: Keymap::new(),
+                    repeat_rate: 25,
+                    repeat_delay: 600,
+                    repeat: None,
+                    held_keys: Vec::new(),
+
+                    touches: Touches::new(),
+
+                    pointer_constraints: null_mut(),
+                    relative_pointer_manager: null_mut(),
+                    relative_pointer: null_mut(),
+                    relative_motion: None,
+
+                    gestures: null_mut(),
+                    gestures_version: 0,
+                    swipe: null_mut(),
+                    pinch: null_mut(),
+                    hold: null_mut(),
+                    gesture_focus: null_mut(),
+                    gesture: None,
+
+                    text_input_manager: null_mut(),
+                    text_input: null_mut(),
+                    text_input_focus: null_mut(),
+                    text_input_enabled: false,
+                    preedit: None,
+                    commit: None,
+                    delete: (0, 0),
+                    current_preedit: (String::new(), None),
+
+                    app_input: crate::ffi::AppInput::new(),
+                    input_queue: Vec::new(),
+                    pointer_frames: false,
+                    pointer_pending: Vec::new(),
+                    pointer_motion: None,
+                    scroll: None,
+                })),
+            });
+            let connection = app.connection;
+            // Initialization With Callback
+            (*connection).client.registry_add_listener(
+                registry,
+                &REGISTRY_LISTENER,
+                connection.cast(),
+            );
+            ((*connection).client.wl_display_roundtrip)(display.as_ptr());
+            // Check for required globals
+            if (*connection).compositor.is_null() {
+                return Err(WindowError::MissingGlobal("wl_compositor"));
+            }
+            if (*connection).shell.is_null() {
+                return Err(WindowError::MissingGlobal("xdg_wm_base"));
+            }
+            // Create cursor surface
+            (*connection).cursor_surface = (*connection)
+                .client
+                .compositor_create_surface((*connection).compositor);
+
+            Ok(app)
+        }
     }
 }
 
-linker!(extern "C" WaylandEGL "libwayland-egl.so.1" {
-    fn wl_egl_window_create(
-        surface: *mut WlSurface,
-        width: c_int,
-        height: c_int,
-    ) -> *mut WlEglWindow;
-    fn wl_egl_window_resize(
-        egl_window: *mut WlEglWindow,
-        width: c_int,
-        height: c_int,
-        dx: c_int,
-        dy: c_int,
-    ) -> ();
-    fn wl_egl_window_destroy(egl_window: *mut WlEglWindow) -> ();
-});
+impl Drop for WaylandApp {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(self.connection));
+        }
+    }
+}
 
-linker!(extern "C" WaylandCursor "libwayland-cursor.so.0" {
-    fn wl_cursor_image_get_buffer(image: *mut WlCursorImage) -> *mut WlBuffer;
-    fn wl_cursor_theme_destroy(theme: *mut WlCursorTheme) -> ();
-    fn wl_cursor_theme_load(
-        name: *const c_char,
-        size: c_int,
-        shm: *mut WlShm,
-    ) -> *mut WlCursorTheme;
-    fn wl_cursor_theme_get_cursor(
-        theme: *mut WlCursorTheme,
-        name: *const c_char,
-    ) -> *mut WlCursor;
-});
+impl crate::Napp for WaylandApp {
+    fn handle(&self) -> crate::NwinHandle {
+        let display = unsafe { (*self.connection).display };
+        crate::NwinHandle::Wayland(display.as_ptr().cast())
+    }
+
+    fn window(
+        self: Rc<Self>,
+        id: crate::WindowId,
+        builder: &crate::WindowBuilder,
+        redraw: crate::Redraw,
+    ) -> Result<Box<dyn crate::Nwin>, WindowError> {
+        Ok(Wayland::new(self, id, builder, redraw))
+    }
+}
 
-// Wrapper around Wayland Libraries
+// A Wayland window.
 pub(super) struct Wayland {
-    //
+    // The window, only set while it's inside `run()` (it may move between
+    // calls), and whether a frame is waiting for it.
     window: *mut crate::Window,
+    frame_pending: bool,
+    id: crate::WindowId,
 
     // Draw
     draw: Option<NonNull<dyn crate::Draw>>,
 
-    // Shared Objects
-    client: WaylandClient,
-    egl: WaylandEGL,
-    cursor: WaylandCursor,
+    // Connection (kept alive as long as the window is)
+    _app: Rc<WaylandApp>,
+    connection: *mut Connection,
 
     // Client
-    display: NonNull<WlDisplay>,
     callback: *mut WlCallback,
-    compositor: *mut WlCompositor,
     surface: *mut WlSurface,
-    cursor_surface: *mut WlSurface,
-    seat: *mut WlSeat,
-    pointer: *mut WlPointer,
-    keyboard: *mut WlKeyboard,
-    touch: *mut WlTouch,
-    shell: *mut ZxdgShell,
     shell_surface: *mut ZxdgSurface,
     toplevel: *mut ZxdgToplevel,
+    decoration: *mut ZxdgToplevelDecoration,
+    decorations: crate::Decorations,
     restore_width: c_int,
     restore_height: c_int,
     window_width: c_int,
     window_height: c_int,
-    refresh_rate: Duration,
-    // FIXME: Event based rather than state based.
-    is_restored: bool,
-    fullscreen: bool,
-    configured: bool,
+    status: crate::WindowStatus,
+    fullscreen_key: bool,
+    close_on_request: bool,
+    closed: bool,
+
+    // Events for `Window::event()`, and input for input listeners.
+    events: VecDeque<crate::WindowEvent>,
+    input: crate::ffi::InputQueue,
 
     // EGL
     egl_window: *mut WlEglWindow,
 
-    // Cursor
-    default_cursor: *mut WlCursor,
-    cursor_theme: *mut WlCursorTheme,
-    shm: *mut WlShm,
+    // Per-frame callback (taken out while it's being called).
+    redraw: Option<crate::Redraw>,
+
+    // Function to calculate if the window should move or resize, and the
+    // result for the latest pointer position.
+    hit_test: Option<crate::HitTest>,
+    region: crate::Region,
+
+    // Built-in frame, drawn when the window manager doesn't draw one, and
+    // which part of it the pointer is over.
+    frame_wanted: bool,
+    frame_hit: Option<crate::frame::FrameHit>,
+    title: String,
+
+    // Text input state, sent to the input method while this window has text
+    // input focus (cursor rectangle & surrounding text in surface pixels &
+    // bytes).
+    text_input: bool,
+    text_cursor: (c_int, c_int, c_int, c_int),
+    surrounding_text: Option<(CString, c_int, c_int)>,
+
+    // Pointer lock or confinement, and whether it's a lock.
+    pointer_constraint: *mut ZwpPointerConstraint,
+    pointer_locked: bool,
+}
 
-    redraw: fn(window: &mut crate::Window, nanos: Duration) -> (),
+impl Wayland {
+    // Draw a frame, and request the next one.  Only called while the window
+    // is inside `run()`.
+    unsafe fn draw_frame(&mut self) {
+        let connection = self.connection;
+        let client = &(*connection).client;
+        let data: *mut Wayland = self;
 
-    // Async event queues.
-    input_queue: Vec<Input>,
+        // Start rendering on the screen.
+        (*self.draw.unwrap().as_ptr()).begin_draw();
 
-    // Function to calculate if the window should move
-    move_: fn(x: f32, y: f32) -> bool,
-    move_state: bool,
-}
+        // Draw user-defined objects.
+        if let Some(mut redraw) = self.redraw.take() {
+            redraw(&mut *self.window, (*connection).refresh_rate);
+            self.redraw = Some(redraw);
+        }
 
-fn move_dummy(_x: f32, _y: f32) -> bool {
-    false
-}
+        // Draw the built-in frame on top.
+        (*self.window).draw_frame();
 
-impl Wayland {
-    pub(super) fn new(
-        name: &str,
-        redraw: fn(window: &mut crate::Window, nanos: Duration) -> (),
-    ) -> Result<Box<Self>, String> {
-        let client = WaylandClient::new()
-            .map_err(|e| format!("Wayland Client {}", e))?;
-        let egl =
-            WaylandEGL::new().map_err(|e| format!("Wayland EGL {}", e))?;
-        let cursor = WaylandCursor::new()
-            .map_err(|e| format!("Wayland Cursor {}", e))?;
+        // Get ready for next frame.
+        self.callback = client.surface_frame(self.surface);
+        client.callback_add_listener(
+            self.callback,
+            &FRAME_LISTENER,
+            data.cast(),
+        );
 
-        // Needed for ZXDG extensions.
-        client.init();
+        // Finish rendering on the screen.
+        (*self.draw.unwrap().as_ptr()).finish_draw();
+    }
+
+    fn new(
+        app: Rc<WaylandApp>,
+        id: crate::WindowId,
+        builder: &crate::WindowBuilder,
+        redraw: crate::Redraw,
+    ) -> Box<Self> {
+        let connection = app.connection;
+        let (width, height) = builder.size;
 
         unsafe {
             // Create window.
-            let display = client.connect().ok_or("Failed to find client")?;
-            let registry = client.display_get_registry(display.as_ptr());
             let mut wayland = Box::new(Wayland {
                 window: std::ptr::null_mut(),
+                frame_pending: false,
+                id,
                 draw: None,
-                client,
-                egl,
-                cursor,
-                display,
+                _app: app,
+                connection,
                 callback: null_mut(),
-                compositor: null_mut(),
                 surface: null_mut(),
-                cursor_surface: null_mut(),
-                seat: null_mut(),
-                pointer: null_mut(),
-                keyboard: null_mut(),
-                touch: null_mut(),
-                shell: null_mut(),
                 shell_surface: null_mut(),
                 toplevel: null_mut(),
-                restore_width: 640,
-                restore_height: 360,
-                window_width: 640,
-                window_height: 360,
-                refresh_rate: Duration::new(0, 0),
-                is_restored: false,
-                fullscreen: false,
-                configured: false,
+                decoration: null_mut(),
+                decorations: crate::Decorations::Client,
+                restore_width: width.into(),
+                restore_height: height.into(),
+                window_width: width.into(),
+                window_height: height.into(),
+                status: crate::WindowStatus::default(),
+                fullscreen_key: builder.fullscreen_key,
+                close_on_request: builder.close_on_request,
+                closed: false,
+
+                events: VecDeque::new(),
+                input: (*connection).app_input.queue(),
+
+                egl_window: null_mut(),
+
+                redraw: Some(redraw),
+
+                hit_test: None,
+                region: crate::Region::Client,
+
+                frame_wanted: builder.frame.is_some(),
+                frame_hit: None,
+                title: builder.title.clone(),
+
+                text_input: false,
+                text_cursor: (0, 0, 0, 0),
+                surrounding_text: None,
+
+                pointer_constraint: null_mut(),
+                pointer_locked: false,
+            });
+            // Wayland window as pointer
+            let window: *mut Wayland = &mut *wayland;
+            let client = &(*connection).client;
+            // Create surface
+            wayland.surface =
+                client.compositor_create_surface((*connection).compositor);
+            // Create shell_surface
+            wayland.shell_surface = if (*connection).stable {
+                client.xdg_wm_base_get_xdg_surface(
+                    (*connection).shell,
+                    wayland.surface,
+                )
+            } else {
+                client.zxdg_shell_v6_get_xdg_surface(
+                    (*connection).shell,
+                    wayland.surface,
+                )
+            };
+            // Add listener to shell_surface
+            client.zxdg_surface_v6_add_listener(
+                wayland.shell_surface,
+                &XDG_SURFACE_LISTENER,
+                window.cast(),
+            );
+            // Create toplevel
+            wayland.toplevel = if (*connection).stable {
+                client.xdg_surface_get_toplevel(wayland.shell_surface)
+            } else {
+                client.zxdg_surface_v6_get_toplevel(wayland.shell_surface)
+            };
+            // Add toplevel listener
+            client.zxdg_toplevel_v6_add_listener(
+                wayland.toplevel,
+                &XDG_TOPLEVEL_LISTENER,
+                window.cast(),
+            );
+            // Ask for server-side decorations (only works with stable).
+            let manager = (*connection).decoration_manager;
+            if (*connection).stable && !manager.is_null() {
+                wayland.decoration = client
+                    .zxdg_decoration_manager_v1_get_toplevel_decoration(
+                        manager,
+                        wayland.toplevel,
+                    );
+                client.zxdg_toplevel_decoration_v1_add_listener(
+                    wayland.decoration,
+                    &DECORATION_LISTENER,
+                    window.cast(),
+                );
+                client.zxdg_toplevel_decoration_v1_set_mode(
+                    wayland.decoration,
+                    builder.decorations,
+                );
+            }
+            // Set Window & App Title (without NUL bytes, see `with_app()`)
+            let window_title = CString::new(builder.title.as_str()).unwrap();
+            let app_id = match builder.app_id {
+                Some(ref app_id) => CString::new(app_id.as_str()).unwrap(),
+                None => window_title.clone(),
+            };
+            client.zxdg_toplevel_v6_set_title(
+                wayland.toplevel,
+                window_title.as_ptr(),
+            );
+            client
+                .zxdg_toplevel_v6_set_app_id(wayland.toplevel, app_id.as_ptr());
+            // Set Size Limits
+            if let Some((width, height)) = builder.min_size {
+                client.zxdg_toplevel_v6_set_min_size(
+                    wayland.toplevel,
+                    width.into(),
+                    height.into(),
+                );
+            }
+            if let Some((width, height)) = builder.max_size {
+                client.zxdg_toplevel_v6_set_max_size(
+                    wayland.toplevel,
+                    width.into(),
+                    height.into(),
+                );
+            }
+            // Set Initial State
+            match builder.state {
+                crate::WindowState::Windowed => {}
+                crate::WindowState::Maximized => {
+                    client.zxdg_toplevel_v6_set_maximized(wayland.toplevel)
+                }
+                crate::WindowState::Fullscreen => {
+                    client.zxdg_toplevel_v6_set_fullscreen(wayland.toplevel)
+                }
+            }
+            // Show Window
+            wayland.callback =
+                client.display_sync((*connection).display.as_ptr());
+            // Window Callbacks
+            client.callback_add_listener(
+                wayland.callback,
+                &FRAME_LISTENER,
+                window.cast(),
+            );
+            // Route input to this window
+            (*connection).windows.push(window);
+
+            wayland
+        }
+    }
+
+    // Convert surface coordinates (24.8 fixed point) to window units.
+    fn window_position(&self, x: i32, y: i32) -> (f32, f32) {
+        let (left, top, _right, _bottom) = self.margins();
+        let (x, y) = (x - left * 256, y - top * 256);
+        let w = (self.window_width as f32 * 256.0).recip();
+
+        (x as f32 * w, y as f32 * w)
+    }
+
+    // If the built-in frame should be drawn.
+    fn framed(&self) -> bool {
+        self.frame_wanted
+            && self.decorations == crate::Decorations::Client
+            && !self.status.fullscreen
+    }
+
+    // Space taken by the built-in frame: (left, top, right, bottom).
+    fn margins(&self) -> (c_int, c_int, c_int, c_int) {
+        if self.framed() {
+            let (left, top, right, bottom) = crate::frame::MARGINS;
+            (left.into(), top.into(), right.into(), bottom.into())
+        } else {
+            (0, 0, 0, 0)
+        }
+    }
+
+    // Size of the whole surface, including the built-in frame.
+    fn surface_size(&self) -> (c_int, c_int) {
+        let (left, top, right, bottom) = self.margins();
+        (
+            self.window_width + left + right,
+            self.window_height + top + bottom,
+        )
+    }
+
+    // Resize the surface to fit the content size and the frame around it.
+    fn apply_size(&mut self) {
+        let (left, _top, _right, bottom) = self.margins();
+        let (width, height) = self.surface_size();
+        unsafe {
+            if !self.egl_window.is_null() {
+                ((*self.connection).egl.wl_egl_window_resize)(
+                    self.egl_window,
+                    width,
+                    height,
+                    0,
+                    0,
+                );
+            }
+            if let Some(draw) = self.draw {
+                let draw = &mut *draw.as_ptr();
+                draw.resize(
+                    self.window_width.try_into().unwrap(),
+                    self.window_height.try_into().unwrap(),
+                );
+                draw.offset(
+                    left.try_into().unwrap(),
+                    bottom.try_into().unwrap(),
+                );
+            }
+        }
+    }
+}
+
+impl Drop for Wayland {
+    fn drop(&mut self) {
+        let connection = self.connection;
+        let this: *mut Wayland = self;
+
+        unsafe {
+            // Input that's already been received still goes to this window.
+            (*connection).flush_input();
+
+            // Stop routing input to this window.
+            (*connection).windows.retain(|window| *window != this);
+            (*connection).touches.remove_window(this);
+            if (*connection).pointer_focus == this {
+                (*connection).pointer_frame();
+                (*connection).pointer_focus = null_mut();
+            }
+            if (*connection).keyboard_focus == this {
+                (*connection).keyboard_focus = null_mut();
+            }
+            if (*connection).gesture_focus == this {
+                (*connection).gesture_focus = null_mut();
+                (*connection).gesture = None;
+            }
+            if (*connection).text_input_focus == this {
+                (*connection).text_input_focus = null_mut();
+            }
+
+            // Window objects, the role (toplevel) first, then the surface.
+            let client = &(*connection).client;
+            if !self.callback.is_null() {
+                client.callback_destroy(self.callback);
+            }
+            if !self.pointer_constraint.is_null() {
+                client.zwp_pointer_v1_destroy(self.pointer_constraint.cast());
+            }
+            if !self.decoration.is_null() {
+                client.zxdg_toplevel_decoration_v1_destroy(self.decoration);
+            }
+            if !self.toplevel.is_null() {
+                client.zxdg_toplevel_v6_destroy(self.toplevel);
+            }
+            if !self.shell_surface.is_null() {
+                client.zxdg_surface_v6_destroy(self.shell_surface);
+            }
+            if !self.egl_window.is_null() {
+                ((*connection).egl.wl_egl_window_destroy)(self.egl_window);
+            }
+            if !self.surface.is_null() {
+                client.surface_destroy(self.surface);
+            }
+            (client.wl_display_flush)((*connection).display.as_ptr());
+        }
+    }
+}
+
+impl crate::Nwin for Wayland {
+    fn connect(
+        &mut self,
+        draw: &mut Box<dyn crate::Draw>,
+    ) -> Result<(), WindowError> {
+        self.draw = NonNull::new(&mut **draw);
+
+        match draw.handle() {
+            crate::DrawHandle::Gl(_c) => {
+                let (width, height) = self.surface_size();
+                self.egl_window = unsafe {
+                    ((*self.connection).egl.wl_egl_window_create)(
+                        self.surface,
+                        width,
+                        height,
+                    )
+                };
+                if self.egl_window.is_null() {
+                    return Err(WindowError::GraphicsContext);
+                }
+            }
+            crate::DrawHandle::Vulkan(_c) => unimplemented!(),
+        }
+        draw.connect(self.egl_window.cast())?;
+        self.apply_size();
+        Ok(())
+    }
+
+    fn run(&mut self, window: *mut crate::Window) -> bool {
+        if self.closed {
+            return false;
+        }
+        self.window = window;
+        unsafe {
+            // Draw the frame that was ready while another window was running.
+            if self.frame_pending {
+                self.frame_pending = false;
+                self.draw_frame();
+            }
+
+            let connection = self.connection;
+            let ret = (*connection).dispatch();
+            (*connection).key_repeat();
+            (*connection).flush_input();
+            self.window = null_mut();
+
+            ret != -1 && !self.closed
+        }
+    }
+
+    fn dimensions(&self) -> (u16, u16) {
+        (self.window_width as u16, self.window_height as u16)
+    }
+
+    fn event(&mut self) -> Option<crate::WindowEvent> {
+        self.events.pop_front()
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) {
+        unsafe {
+            let client = &(*self.connection).client;
+            if fullscreen {
+                client.zxdg_toplevel_v6_set_fullscreen(self.toplevel);
+            } else {
+                client.zxdg_toplevel_v6_unset_fullscreen(self.toplevel);
+            }
+        }
+    }
+
+    fn set_maximized(&mut self, maximized: bool) {
+        unsafe {
+            let client = &(*self.connection).client;
+            if maximized {
+                client.zxdg_toplevel_v6_set_maximized(self.toplevel);
+            } else {
+                client.zxdg_toplevel_v6_unset_maximized(self.toplevel);
+            }
+        }
+    }
+
+    fn minimize(&mut self) {
+        unsafe {
+            (*self.connection)
+                .client
+                .zxdg_toplevel_v6_set_minimized(self.toplevel);
+        }
+    }
+
+    fn status(&self) -> crate::WindowStatus {
+        self.status
+    }
+
+    fn set_title(&mut self, title: &str) -> Result<(), WindowError> {
+        let c_title =
+            CString::new(title).map_err(|_| WindowError::InvalidString)?;
+        self.title = title.to_string();
+        unsafe {
+            (*self.connection)
+                .client
+                .zxdg_toplevel_v6_set_title(self.toplevel, c_title.as_ptr());
+        }
+        Ok(())
+    }
+
+    fn set_decorations(&mut self, decorations: crate::Decorations) {
+        if self.decoration.is_null() {
+            return;
+        }
+        unsafe {
+            (*self.connection)
+                .client
+                .zxdg_toplevel_decoration_v1_set_mode(
+                    self.decoration,
+                    decorations,
+                );
+        }
+    }
+
+    fn decorations(&self) -> crate::Decorations {
+        self.decorations
+    }
+
+    fn caps_lock(&self) -> bool {
+        unsafe { (*self.connection).keymap.caps_lock() }
+    }
+
+    fn num_lock(&self) -> bool {
+        unsafe { (*self.connection).keymap.num_lock() }
+    }
 
-                egl_window: null_mut(),
+    fn set_frame(&mut self, frame: bool) {
+        self.frame_wanted = frame;
+        self.frame_hit = None;
+        self.apply_size();
+    }
 
-                default_cursor: null_mut(),
-                cursor_theme: null_mut(),
-                shm: null_mut(),
+    fn frame(&self) -> Option<crate::frame::FrameInfo<'_>> {
+        if !self.framed() {
+            return None;
+        }
+        let (width, height) = self.surface_size();
+        let hover = match self.frame_hit {
+            Some(crate::frame::FrameHit::Button(button)) => Some(button),
+            _ => None,
+        };
 
-                redraw,
+        Some(crate::frame::FrameInfo {
+            size: (width.try_into().unwrap(), height.try_into().unwrap()),
+            title: &self.title,
+            active: self.status.activated,
+            hover,
+        })
+    }
 
-                input_queue: Vec::new(),
+    fn set_text_input(&mut self, enabled: bool) {
+        self.text_input = enabled;
+        unsafe { (*self.connection).text_input_update(self) }
+    }
 
-                move_state: false,
-                move_: move_dummy,
-            });
-            // Wayland window as pointer
-            let window: *mut Wayland = &mut *wayland;
-            // Initialization With Callback
-            wayland.client.registry_add_listener(
-                registry,
-                &REGISTRY_LISTENER,
-                window.cast(),
-            );
-            (wayland.client.wl_display_dispatch)(display.as_ptr());
-            // Create surfaces
-            wayland.surface =
-                wayland.client.compositor_create_surface(wayland.compositor);
-            wayland.cursor_surface =
-                wayland.client.compositor_create_surface(wayland.compositor);
-            // Create shell_surface
-            wayland.shell_surface = wayland
-                .client
-                .zxdg_shell_v6_get_xdg_surface(wayland.shell, wayland.surface);
-            // Add listener to shell_surface
-            wayland.client.zxdg_surface_v6_add_listener(
-                wayland.shell_surface,
-                &XDG_SURFACE_LISTENER,
-                window.cast(),
-            );
-            // Create toplevel
-            wayland.toplevel = wayland
-                .client
-                .zxdg_surface_v6_get_toplevel(wayland.shell_surface);
-            // Add toplevel listener
-            wayland.client.zxdg_toplevel_v6_add_listener(
-                wayland.toplevel,
-                &XDG_TOPLEVEL_LISTENER,
-                window.cast(),
-            );
-            // Set Window & App Title
-            let window_title = CString::new(name).unwrap();
-            wayland.client.zxdg_toplevel_v6_set_title(
-                wayland.toplevel,
-                window_title.as_ptr(),
-            );
-            wayland.client.zxdg_toplevel_v6_set_app_id(
-                wayland.toplevel,
-                window_title.as_ptr(),
-            );
-            // Maximize Window
-            wayland
-                .client
-                .zxdg_toplevel_v6_set_maximized(wayland.toplevel);
-            // Show Window
-            let callback =
-                wayland.client.display_sync(wayland.display.as_ptr());
-            // Window Callbacks
-            wayland.client.callback_add_listener(
-                callback,
-                &FRAME_LISTENER,
-                window.cast(),
-            );
+    fn set_text_cursor(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let w = self.window_width as f32;
+        let (left, top, _right, _bottom) = self.margins();
+        self.text_cursor = (
+            (x * w) as c_int + left,
+            (y * w) as c_int + top,
+            (width * w) as c_int,
+            (height * w) as c_int,
+        );
+        unsafe { (*self.connection).text_input_update(self) }
+    }
 
-            Ok(wayland)
+    fn set_surrounding_text(
+        &mut self,
+        text: &str,
+        cursor: usize,
+        anchor: usize,
+    ) -> Result<(), WindowError> {
+        // The protocol limits surrounding text to under 4000 bytes.
+        if text.len() >= 4000
+            || !text.is_char_boundary(cursor)
+            || !text.is_char_boundary(anchor)
+        {
+            return Err(WindowError::InvalidString);
         }
+        let text =
+            CString::new(text).map_err(|_| WindowError::InvalidString)?;
+        // Offsets are under 4000, so they fit.
+        self.surrounding_text = Some((text, cursor as c_int, anchor as c_int));
+        unsafe { (*self.connection).text_input_update(self) }
+        Ok(())
     }
-}
 
-impl crate::Nwin for Wayland {
-    fn handle(&self) -> crate::NwinHandle {
-        crate::NwinHandle::Wayland(self.display.as_ptr().cast())
+    fn lock_pointer(&mut self, confine: bool) {
+        self.unlock_pointer(None);
+        unsafe {
+            let connection = &*self.connection;
+            if connection.pointer_constraints.is_null()
+                || connection.pointer.is_null()
+            {
+                return;
+            }
+            let data: *mut Wayland = self;
+            self.pointer_constraint =
+                connection.client.zwp_pointer_constraints_v1_constrain(
+                    connection.pointer_constraints,
+                    confine,
+                    self.surface,
+                    connection.pointer,
+                );
+            connection.client.zwp_pointer_constraint_v1_add_listener(
+                self.pointer_constraint,
+                &CONSTRAINT_LISTENER,
+                data.cast(),
+            );
+        }
+        self.pointer_locked = !confine;
     }
 
-    fn connect(&mut self, draw: &mut Box<dyn crate::Draw>) {
-        self.draw = NonNull::new(&mut **draw);
-
-        match draw.handle() {
-            crate::DrawHandle::Gl(_c) => {
-                self.egl_window = unsafe {
-                    (self.egl.wl_egl_window_create)(
-                        self.surface,
-                        self.window_width,
-                        self.window_height,
-                    )
-                };
+    fn unlock_pointer(&mut self, hint: Option<(f32, f32)>) {
+        if self.pointer_constraint.is_null() {
+            return;
+        }
+        unsafe {
+            let client = &(*self.connection).client;
+            if let (Some((x, y)), true) = (hint, self.pointer_locked) {
+                // Surface coordinates, in 24.8 fixed point.
+                let w = self.window_width as f32;
+                let (left, top, _right, _bottom) = self.margins();
+                client.zwp_locked_pointer_v1_set_cursor_position_hint(
+                    self.pointer_constraint,
+                    ((x * w + left as f32) * 256.0) as c_int,
+                    ((y * w + top as f32) * 256.0) as c_int,
+                );
+                // The hint is applied when the surface is committed.
+                client.surface_commit(self.surface);
             }
-            crate::DrawHandle::Vulkan(_c) => unimplemented!(),
+            client.zwp_pointer_v1_destroy(self.pointer_constraint.cast());
         }
-        draw.connect(self.egl_window.cast());
+        self.pointer_constraint = null_mut();
     }
 
-    fn run(&mut self, window: *mut crate::Window) -> bool {
-        self.window = window;
-        let ret =
-            unsafe { (self.client.wl_display_dispatch)(self.display.as_ptr()) };
-        if !self.input_queue.is_empty() {
-            let mut input_queue = Vec::new();
-            std::mem::swap(&mut input_queue, &mut self.input_queue);
-            unsafe { crate::ffi::push_inputs(input_queue) };
+    fn set_hit_test(&mut self, hit_test: Option<crate::HitTest>) {
+        self.hit_test = hit_test;
+        self.region = crate::Region::Client;
+    }
+
+    fn begin_resize(&mut self, edge: crate::ResizeEdge) {
+        unsafe {
+            let connection = &*self.connection;
+            connection.client.zxdg_toplevel_v6_resize(
+                self.toplevel,
+                connection.seat,
+                connection.serial,
+                edge,
+            );
         }
+    }
 
-        ret != -1
+    fn show_window_menu(&mut self, x: f32, y: f32) {
+        let w = self.window_width as f32;
+        let (left, top, _right, _bottom) = self.margins();
+        unsafe {
+            let connection = &*self.connection;
+            connection.client.zxdg_toplevel_v6_show_window_menu(
+                self.toplevel,
+                connection.seat,
+                connection.serial,
+                (x * w) as i32 + left,
+                (y * w) as i32 + top,
+            );
+        }
     }
 
-    fn dimensions(&self) -> (u16, u16) {
-        (self.window_width as u16, self.window_height as u16)
+    fn set_app_id(&mut self, app_id: &str) -> Result<(), WindowError> {
+        let app_id =
+            CString::new(app_id).map_err(|_| WindowError::InvalidString)?;
+        unsafe {
+            (*self.connection)
+                .client
+                .zxdg_toplevel_v6_set_app_id(self.toplevel, app_id.as_ptr());
+        }
+        Ok(())
     }
 }
 
 extern "C" fn registry_global(
-    window: *mut c_void,
+    connection: *mut c_void,
     registry: *mut WlRegistry,
     name: u32,
     interface: *const c_char,
-    _version: u32,
+    version: u32,
 ) {
-    let window: *mut Wayland = window.cast();
+    let connection: *mut Connection = connection.cast();
 
     unsafe {
         let interface =
@@ -1456,190 +3917,626 @@ extern "C" fn registry_global(
 
         match interface {
             "wl_compositor" => {
-                (*window).compositor = (*window)
+                (*connection).compositor = (*connection)
+                    .client
+                    .registry_bind(
+                        registry,
+                        name,
+                        (*connection).client.wl_compositor_interface,
+                        1,
+                    )
+                    .cast();
+            }
+            "xdg_wm_base" => {
+                // Prefer stable over v6, whichever is announced first.
+                if !(*connection).shell.is_null() {
+                    (*connection)
+                        .client
+                        .zxdg_shell_v6_destroy((*connection).shell);
+                }
+                (*connection).stable = true;
+                (*connection).shell = (*connection)
+                    .client
+                    .registry_bind(
+                        registry,
+                        name,
+                        addr_of!(XDG_WM_BASE_INTERFACE),
+                        version.min(2),
+                    )
+                    .cast();
+                (*connection).client.zxdg_shell_v6_add_listener(
+                    (*connection).shell,
+                    &XDG_SHELL_LISTENER,
+                    connection.cast(),
+                );
+            }
+            "zxdg_decoration_manager_v1" => {
+                (*connection).decoration_manager = (*connection)
+                    .client
+                    .registry_bind(
+                        registry,
+                        name,
+                        addr_of!(ZXDG_DECORATION_MANAGER_V1_INTERFACE),
+                        1,
+                    )
+                    .cast();
+            }
+            "zwp_pointer_constraints_v1" => {
+                (*connection).pointer_constraints = (*connection)
+                    .client
+                    .registry_bind(
+                        registry,
+                        name,
+                        addr_of!(ZWP_POINTER_CONSTRAINTS_V1_INTERFACE),
+                        1,
+                    )
+                    .cast();
+            }
+            "zwp_relative_pointer_manager_v1" => {
+                (*connection).relative_pointer_manager = (*connection)
+                    .client
+                    .registry_bind(
+                        registry,
+                        name,
+                        addr_of!(ZWP_RELATIVE_POINTER_MANAGER_V1_INTERFACE),
+                        1,
+                    )
+                    .cast();
+                (*connection).bind_relative_pointer();
+            }
+            "zwp_pointer_gestures_v1" => {
+                (*connection).gestures_version = version.min(3);
+                (*connection).gestures = (*connection)
+                    .client
+                    .registry_bind(
+                        registry,
+                        name,
+                        addr_of!(ZWP_POINTER_GESTURES_V1_INTERFACE),
+                        (*connection).gestures_version,
+                    )
+                    .cast();
+                (*connection).bind_gestures();
+            }
+            "zwp_text_input_manager_v3" => {
+                (*connection).text_input_manager = (*connection)
                     .client
                     .registry_bind(
                         registry,
                         name,
-                        (*window).client.wl_compositor_interface,
+                        addr_of!(ZWP_TEXT_INPUT_MANAGER_V3_INTERFACE),
                         1,
                     )
                     .cast();
+                (*connection).bind_text_input();
             }
-            "zxdg_shell_v6" => {
-                (*window).shell = (*window)
+            "zxdg_shell_v6" if (*connection).shell.is_null() => {
+                (*connection).shell = (*connection)
                     .client
                     .registry_bind(registry, name, &ZXDG_SHELL_V6_INTERFACE, 1)
                     .cast();
-                (*window).client.zxdg_shell_v6_add_listener(
-                    (*window).shell,
+                (*connection).client.zxdg_shell_v6_add_listener(
+                    (*connection).shell,
                     &XDG_SHELL_LISTENER,
-                    window.cast(),
+                    connection.cast(),
                 );
             }
             "wl_seat" => {
-                (*window).seat = (*window)
+                (*connection).seat = (*connection)
                     .client
                     .registry_bind(
                         registry,
                         name,
-                        (*window).client.wl_seat_interface,
-                        1,
+                        (*connection).client.wl_seat_interface,
+                        version.min(8),
                     )
                     .cast();
+                (*connection).pointer_frames = version >= 5;
 
-                (*window).client.seat_add_listener(
-                    (*window).seat,
+                (*connection).client.seat_add_listener(
+                    (*connection).seat,
                     &SEAT_LISTENER,
-                    window.cast(),
+                    connection.cast(),
                 );
+                (*connection).bind_text_input();
             }
             "wl_shm" => {
-                (*window).shm = (*window)
+                (*connection).shm = (*connection)
                     .client
                     .registry_bind(
                         registry,
                         name,
-                        (*window).client.wl_shm_interface,
+                        (*connection).client.wl_shm_interface,
                         1,
                     )
                     .cast();
 
-                (*window).cursor_theme = ((*window)
-                    .cursor
-                    .wl_cursor_theme_load)(
-                    null_mut(), 16, (*window).shm
-                );
+                (*connection).cursor_theme =
+                    ((*connection).cursor.wl_cursor_theme_load)(
+                        null_mut(),
+                        16,
+                        (*connection).shm,
+                    );
 
-                if (*window).cursor_theme.is_null() {
+                if (*connection).cursor_theme.is_null() {
                     eprintln!("unable to load default theme");
+                    return;
                 }
 
                 static LEFT_PTR: &[u8] = b"left_ptr\0";
 
-                (*window).default_cursor =
-                    ((*window).cursor.wl_cursor_theme_get_cursor)(
-                        (*window).cursor_theme,
+                (*connection).default_cursor =
+                    ((*connection).cursor.wl_cursor_theme_get_cursor)(
+                        (*connection).cursor_theme,
                         CStr::from_bytes_with_nul(LEFT_PTR).unwrap().as_ptr(),
                     );
-                if (*window).default_cursor.is_null() {
-                    panic!("unable to load default left pointer");
+                if (*connection).default_cursor.is_null() {
+                    eprintln!("unable to load default left pointer");
                 }
             }
             "wl_output" => {
-                let output = (*window)
+                let output = (*connection)
                     .client
                     .registry_bind(
                         registry,
                         name,
-                        (*window).client.wl_output_interface,
+                        (*connection).client.wl_output_interface,
                         1,
                     )
                     .cast();
 
-                (*window).client.output_add_listener(
-                    output,
-                    &OUTPUT_LISTENER,
-                    window.cast(),
-                );
-            }
-            _ => {}
-        }
-    }
+                (*connection).client.output_add_listener(
+                    output,
+                    &OUTPUT_LISTENER,
+                    connection.cast(),
+                );
+                (*connection).outputs.push(output);
+            }
+            _ => {}
+        }
+    }
+}
+
+extern "C" fn registry_global_remove(
+    _data: *mut c_void,
+    _registry: *mut WlRegistry,
+    _name: u32,
+) {
+}
+
+extern "C" fn surface_configure(
+    window: *mut c_void,
+    zxdg_surface_v6: *mut ZxdgSurface,
+    serial: u32,
+) {
+    let window: *mut Wayland = window.cast();
+
+    unsafe {
+        (*(*window).connection)
+            .client
+            .zxdg_surface_v6_ack_configure(zxdg_surface_v6, serial);
+    }
+}
+
+extern "C" fn toplevel_configure(
+    window: *mut c_void,
+    _zxdg_toplevel_v6: *mut ZxdgToplevel,
+    width: i32,
+    height: i32,
+    states: *mut WlArray,
+) {
+    let window: *mut Wayland = window.cast();
+
+    unsafe {
+        let states = if (*states).data.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(
+                (*states).data as *const u32,
+                (*states).size / std::mem::size_of::<u32>(),
+            )
+        };
+        let status = decode_states(states);
+        let floating = !(status.maximized
+            || status.fullscreen
+            || status.tiled_left
+            || status.tiled_right
+            || status.tiled_top
+            || status.tiled_bottom);
+        let changed = status != (*window).status;
+        (*window).status = status;
+
+        // 0x0 means the client chooses, so use the size from before the
+        // window was maximized, fullscreen or tiled.  The built-in frame
+        // (if any) is part of the configured size.
+        let (left, top, right, bottom) = (*window).margins();
+        let (width, height) = if width != 0 && height != 0 {
+            let width = (width - left - right).max(1);
+            let height = (height - top - bottom).max(1);
+            if floating {
+                (*window).restore_width = width;
+                (*window).restore_height = height;
+            }
+            (width, height)
+        } else {
+            ((*window).restore_width, (*window).restore_height)
+        };
+
+        (*window).window_width = width;
+        (*window).window_height = height;
+        (*window).apply_size();
+
+        if changed {
+            (*window)
+                .events
+                .push_back(crate::WindowEvent::StateChanged(status));
+        }
+    }
+}
+
+// Decode toplevel states (an array of u32 enum values).
+fn decode_states(states: &[u32]) -> crate::WindowStatus {
+    let mut status = crate::WindowStatus::default();
+    for state in states {
+        match state {
+            1 => status.maximized = true,
+            2 => status.fullscreen = true,
+            3 => status.resizing = true,
+            4 => status.activated = true,
+            5 => status.tiled_left = true,
+            6 => status.tiled_right = true,
+            7 => status.tiled_top = true,
+            8 => status.tiled_bottom = true,
+            _ => {}
+        }
+    }
+    status
+}
+
+extern "C" fn decoration_configure(
+    window: *mut c_void,
+    _decoration: *mut ZxdgToplevelDecoration,
+    mode: u32,
+) {
+    let window: &mut Wayland = unsafe { &mut *window.cast() };
+    let decorations = match mode {
+        2 => crate::Decorations::Server,
+        _ => crate::Decorations::Client,
+    };
+
+    if decorations != window.decorations {
+        window.decorations = decorations;
+        window.frame_hit = None;
+        window.apply_size();
+        window
+            .events
+            .push_back(crate::WindowEvent::DecorationsChanged(decorations));
+    }
+}
+
+extern "C" fn constraint_active(
+    window: *mut c_void,
+    _constraint: *mut ZwpPointerConstraint,
+) {
+    let window: &mut Wayland = unsafe { &mut *window.cast() };
+
+    window
+        .events
+        .push_back(crate::WindowEvent::PointerLockChanged(true));
+}
+
+extern "C" fn constraint_inactive(
+    window: *mut c_void,
+    _constraint: *mut ZwpPointerConstraint,
+) {
+    let window: &mut Wayland = unsafe { &mut *window.cast() };
+
+    window
+        .events
+        .push_back(crate::WindowEvent::PointerLockChanged(false));
+}
+
+extern "C" fn relative_motion(
+    connection: *mut c_void,
+    _relative_pointer: *mut ZwpRelativePointer,
+    utime_hi: u32,
+    utime_lo: u32,
+    dx: i32,
+    dy: i32,
+    dx_unaccel: i32,
+    dy_unaccel: i32,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let time = u64::from(utime_hi) << 32 | u64::from(utime_lo);
+    // Values are in 24.8 fixed point.
+    let motion =
+        connection
+            .relative_motion
+            .get_or_insert(crate::RelativeMotion {
+                dx: 0.0,
+                dy: 0.0,
+                dx_unaccel: 0.0,
+                dy_unaccel: 0.0,
+                time,
+            });
+
+    motion.dx += dx as f32 / 256.0;
+    motion.dy += dy as f32 / 256.0;
+    motion.dx_unaccel += dx_unaccel as f32 / 256.0;
+    motion.dy_unaccel += dy_unaccel as f32 / 256.0;
+    motion.time = time;
+}
+
+extern "C" fn swipe_begin(
+    connection: *mut c_void,
+    _gesture: *mut ZwpPointerGesture,
+    _serial: u32,
+    time: u32,
+    surface: *mut WlSurface,
+    fingers: u32,
+) {
+    gesture_begin(
+        connection,
+        crate::GestureKind::Swipe,
+        time,
+        surface,
+        fingers,
+    );
+}
+
+extern "C" fn pinch_begin(
+    connection: *mut c_void,
+    _gesture: *mut ZwpPointerGesture,
+    _serial: u32,
+    time: u32,
+    surface: *mut WlSurface,
+    fingers: u32,
+) {
+    gesture_begin(
+        connection,
+        crate::GestureKind::Pinch,
+        time,
+        surface,
+        fingers,
+    );
+}
+
+extern "C" fn hold_begin(
+    connection: *mut c_void,
+    _gesture: *mut ZwpPointerGesture,
+    _serial: u32,
+    time: u32,
+    surface: *mut WlSurface,
+    fingers: u32,
+) {
+    gesture_begin(connection, crate::GestureKind::Hold, time, surface, fingers);
+}
+
+fn gesture_begin(
+    connection: *mut c_void,
+    kind: crate::GestureKind,
+    time: u32,
+    surface: *mut WlSurface,
+    fingers: u32,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let gesture = crate::Gesture {
+        kind,
+        phase: crate::GesturePhase::Begin,
+        fingers,
+        dx: 0.0,
+        dy: 0.0,
+        scale: 1.0,
+        rotation: 0.0,
+        time,
+    };
+
+    connection.gesture_focus = connection.window(surface);
+    connection.gesture = Some(gesture);
+    connection.gesture_event(gesture, 1.0);
+}
+
+extern "C" fn swipe_update(
+    connection: *mut c_void,
+    _gesture: *mut ZwpPointerGesture,
+    time: u32,
+    dx: i32,
+    dy: i32,
+) {
+    gesture_update(connection, time, dx, dy, None, 0);
+}
+
+extern "C" fn pinch_update(
+    connection: *mut c_void,
+    _gesture: *mut ZwpPointerGesture,
+    time: u32,
+    dx: i32,
+    dy: i32,
+    scale: i32,
+    rotation: i32,
+) {
+    gesture_update(connection, time, dx, dy, Some(scale), rotation);
+}
+
+// Values are in 24.8 fixed point.
+fn gesture_update(
+    connection: *mut c_void,
+    time: u32,
+    dx: i32,
+    dy: i32,
+    scale: Option<i32>,
+    rotation: i32,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let gesture = match connection.gesture {
+        Some(ref mut gesture) => gesture,
+        None => return,
+    };
+
+    let previous_scale = gesture.scale.max(f32::MIN_POSITIVE);
+    gesture.phase = crate::GesturePhase::Update;
+    gesture.dx = dx as f32 / 256.0;
+    gesture.dy = dy as f32 / 256.0;
+    if let Some(scale) = scale {
+        gesture.scale = scale as f32 / 256.0;
+    }
+    gesture.rotation = rotation as f32 / 256.0;
+    gesture.time = time;
+    let gesture = *gesture;
+    connection.gesture_event(gesture, gesture.scale / previous_scale);
+}
+
+extern "C" fn gesture_end(
+    connection: *mut c_void,
+    _gesture: *mut ZwpPointerGesture,
+    _serial: u32,
+    time: u32,
+    cancelled: i32,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let mut gesture = match connection.gesture.take() {
+        Some(gesture) => gesture,
+        None => return,
+    };
+
+    gesture.phase = if cancelled != 0 {
+        crate::GesturePhase::Cancel
+    } else {
+        crate::GesturePhase::End
+    };
+    gesture.dx = 0.0;
+    gesture.dy = 0.0;
+    gesture.rotation = 0.0;
+    gesture.time = time;
+    connection.gesture_event(gesture, 1.0);
+    connection.gesture_focus = null_mut();
+}
+
+extern "C" fn text_input_enter(
+    connection: *mut c_void,
+    _text_input: *mut ZwpTextInput,
+    surface: *mut WlSurface,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+
+    connection.text_input_focus = connection.window(surface);
+    connection.text_input_update(connection.text_input_focus);
 }
 
-extern "C" fn registry_global_remove(
-    _data: *mut c_void,
-    _registry: *mut WlRegistry,
-    _name: u32,
+extern "C" fn text_input_leave(
+    connection: *mut c_void,
+    _text_input: *mut ZwpTextInput,
+    _surface: *mut WlSurface,
 ) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+
+    // The compositor disables text input on leave.
+    connection.clear_preedit();
+    connection.text_input_focus = null_mut();
+    connection.text_input_enabled = false;
 }
 
-extern "C" fn surface_configure(
-    window: *mut c_void,
-    zxdg_surface_v6: *mut ZxdgSurface,
-    serial: u32,
+extern "C" fn text_input_preedit_string(
+    connection: *mut c_void,
+    _text_input: *mut ZwpTextInput,
+    text: *const c_char,
+    cursor_begin: i32,
+    cursor_end: i32,
 ) {
-    let window: *mut Wayland = window.cast();
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let text = if text.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(text) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    // Both are -1 when the cursor should be hidden.
+    let cursor = match (cursor_begin.try_into(), cursor_end.try_into()) {
+        (Ok(begin), Ok(end)) => Some((begin, end)),
+        _ => None,
+    };
 
-    unsafe {
-        (*window)
-            .client
-            .zxdg_surface_v6_ack_configure(zxdg_surface_v6, serial);
-    }
+    connection.preedit = Some((text, cursor));
 }
 
-extern "C" fn toplevel_configure(
-    window: *mut c_void,
-    _zxdg_toplevel_v6: *mut ZxdgToplevel,
-    width: i32,
-    height: i32,
-    _states: *mut WlArray,
+extern "C" fn text_input_commit_string(
+    connection: *mut c_void,
+    _text_input: *mut ZwpTextInput,
+    text: *const c_char,
 ) {
-    let window: *mut Wayland = window.cast();
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
 
-    unsafe {
-        if !(*window).egl_window.is_null() && (*window).configured {
-            ((*window).egl.wl_egl_window_resize)(
-                (*window).egl_window,
-                width,
-                height,
-                0,
-                0,
-            );
-            (*window).configured = false;
-            (*window).window_width = width;
-            (*window).window_height = height;
-        } else if (*window).fullscreen {
-        } else if width != 0 && height != 0 {
-            if (*window).is_restored {
-                (*window).restore_width = (*window).window_width;
-                (*window).restore_height = (*window).window_height;
-            }
-            (*window).is_restored = false;
-            if !(*window).egl_window.is_null() {
-                ((*window).egl.wl_egl_window_resize)(
-                    (*window).egl_window,
-                    width,
-                    height,
-                    0,
-                    0,
-                );
-            }
-            (*window).window_width = width;
-            (*window).window_height = height;
-        } else {
-            (*window).window_width = (*window).restore_width;
-            (*window).window_height = (*window).restore_height;
-            (*window).is_restored = true;
-            if !(*window).egl_window.is_null() {
-                ((*window).egl.wl_egl_window_resize)(
-                    (*window).egl_window,
-                    (*window).restore_width,
-                    (*window).restore_height,
-                    0,
-                    0,
-                );
-            }
-        }
+    connection.commit = if text.is_null() {
+        None
+    } else {
+        Some(
+            unsafe { CStr::from_ptr(text) }
+                .to_string_lossy()
+                .into_owned(),
+        )
+    };
+}
 
-        if let Some(draw) = (*window).draw {
-            (*draw.as_ptr()).resize(
-                (*window).window_width.try_into().unwrap(),
-                (*window).window_height.try_into().unwrap(),
-            );
+extern "C" fn text_input_delete_surrounding_text(
+    connection: *mut c_void,
+    _text_input: *mut ZwpTextInput,
+    before_length: u32,
+    after_length: u32,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+
+    connection.delete = (before_length, after_length);
+}
+
+extern "C" fn text_input_done(
+    connection: *mut c_void,
+    _text_input: *mut ZwpTextInput,
+    _serial: u32,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    // Anything not sent since the last `done` goes back to its initial value.
+    let preedit = connection.preedit.take().unwrap_or((String::new(), None));
+    let commit = connection.commit.take();
+    let (before, after) = std::mem::take(&mut connection.delete);
+    let wayland = connection.text_input_focus;
+    if wayland.is_null() {
+        return;
+    }
+    let window: &mut Wayland = unsafe { &mut *wayland };
+
+    // Applied in the order given by the protocol.
+    if (before, after) != (0, 0) {
+        window
+            .events
+            .push_back(crate::WindowEvent::DeleteSurroundingText {
+                before: before.try_into().unwrap(),
+                after: after.try_into().unwrap(),
+            });
+    }
+    if let Some(commit) = commit {
+        for c in commit.chars() {
+            connection.input_queue.push((window.id, Input::Text(c)));
         }
     }
+    if preedit != connection.current_preedit {
+        connection.current_preedit = preedit.clone();
+        window.events.push_back(crate::WindowEvent::Preedit {
+            text: preedit.0,
+            cursor: preedit.1,
+        });
+    }
 }
 
 extern "C" fn toplevel_close(
     window: *mut c_void,
     _zxdg_toplevel_v6: *mut ZxdgToplevel,
 ) {
-    let _window: &mut Wayland = unsafe { &mut *window.cast() };
+    let window: &mut Wayland = unsafe { &mut *window.cast() };
 
-    // FIXME: Separate type of event for closing window.
-    todo!();
+    window.events.push_back(crate::WindowEvent::CloseRequested);
+    if window.close_on_request {
+        window.closed = true;
+    }
 }
 
 extern "C" fn output_geometry(
@@ -1664,11 +4561,11 @@ extern "C" fn output_mode(
     _height: i32, // Monitor height (in pixels)
     refresh: i32,
 ) {
-    let window: *mut Wayland = data.cast();
+    let connection: *mut Connection = data.cast();
 
     unsafe {
         // Convert from frames per 1000 seconds to `Duration` per frame.
-        (*window).refresh_rate =
+        (*connection).refresh_rate =
             Duration::from_secs(1000).div_f64(refresh as f64);
     }
 }
@@ -1683,404 +4580,604 @@ extern "C" fn output_scale(
 }
 
 extern "C" fn seat_handle_capabilities(
-    window: *mut c_void,
+    connection: *mut c_void,
     seat: *mut WlSeat,
     caps: u32,
 ) {
     unsafe {
-        let window: *mut Wayland = window.cast();
+        let connection: *mut Connection = connection.cast();
 
         // Allow Pointer Events
         let has_pointer = (caps & WlSeatCapability::Pointer as u32) != 0;
-        if has_pointer && (*window).pointer.is_null() {
-            (*window).pointer = (*window).client.seat_get_pointer(seat);
+        if has_pointer && (*connection).pointer.is_null() {
+            (*connection).pointer = (*connection).client.seat_get_pointer(seat);
 
-            (*window).client.pointer_add_listener(
-                (*window).pointer,
+            (*connection).client.pointer_add_listener(
+                (*connection).pointer,
                 &POINTER_LISTENER,
-                window.cast(),
+                connection.cast(),
+            );
+            (*connection).bind_gestures();
+            (*connection).bind_relative_pointer();
+        } else if !has_pointer && !(*connection).pointer.is_null() {
+            (*connection).unbind_gestures();
+            (*connection).unbind_relative_pointer();
+            ((*connection).client.wl_proxy_destroy)(
+                (*connection).pointer.cast(),
             );
-        } else if !has_pointer && !(*window).pointer.is_null() {
-            ((*window).client.wl_proxy_destroy)((*window).pointer.cast());
-            (*window).pointer = std::ptr::null_mut();
+            (*connection).pointer = std::ptr::null_mut();
         }
 
         // Allow Keyboard Events
         let has_keyboard = (caps & WlSeatCapability::Keyboard as u32) != 0;
-        if has_keyboard && (*window).keyboard.is_null() {
-            (*window).keyboard = (*window).client.seat_get_keyboard(seat);
-            (*window).client.keyboard_add_listener(
-                (*window).keyboard,
+        if has_keyboard && (*connection).keyboard.is_null() {
+            (*connection).keyboard =
+                (*connection).client.seat_get_keyboard(seat);
+            (*connection).client.keyboard_add_listener(
+                (*connection).keyboard,
                 &KEYBOARD_LISTENER,
-                window.cast(),
+                connection.cast(),
+            );
+        } else if !has_keyboard && !(*connection).keyboard.is_null() {
+            ((*connection).client.wl_proxy_destroy)(
+                (*connection).keyboard.cast(),
             );
-        } else if !has_keyboard && !(*window).keyboard.is_null() {
-            ((*window).client.wl_proxy_destroy)((*window).keyboard.cast());
-            (*window).keyboard = std::ptr::null_mut();
+            (*connection).keyboard = std::ptr::null_mut();
         }
 
         let has_touch = (caps & WlSeatCapability::Touch as u32) != 0;
-        if has_touch && (*window).touch.is_null() {
-            (*window).touch = (*window).client.seat_get_touch(seat);
-            (*window).client.touch_add_listener(
-                (*window).touch,
+        if has_touch && (*connection).touch.is_null() {
+            (*connection).touch = (*connection).client.seat_get_touch(seat);
+            (*connection).client.touch_add_listener(
+                (*connection).touch,
                 &TOUCH_LISTENER,
-                window.cast(),
+                connection.cast(),
             );
-        } else if !has_touch && !(*window).touch.is_null() {
-            ((*window).client.wl_proxy_destroy)((*window).touch.cast());
-            (*window).touch = std::ptr::null_mut();
+        } else if !has_touch && !(*connection).touch.is_null() {
+            ((*connection).client.wl_proxy_destroy)((*connection).touch.cast());
+            (*connection).touch = std::ptr::null_mut();
         }
     }
 }
 
+extern "C" fn seat_handle_name(
+    _connection: *mut c_void,
+    _seat: *mut WlSeat,
+    _name: *const c_char,
+) {
+}
+
 extern "C" fn handle_xdg_shell_ping(
-    window: *mut c_void,
+    connection: *mut c_void,
     shell: *mut ZxdgShell,
     serial: u32,
 ) {
-    let window: *mut Wayland = window.cast();
+    let connection: *mut Connection = connection.cast();
 
     unsafe {
-        (*window).client.zxdg_shell_v6_pong(shell, serial);
+        (*connection).client.zxdg_shell_v6_pong(shell, serial);
     }
 }
 
 extern "C" fn touch_handle_down(
-    _window: *mut c_void,
+    connection: *mut c_void,
     _touch: *mut WlTouch,
-    _serial: u32,
+    serial: u32,
     _time: u32,
-    _surface: *mut WlSurface,
+    surface: *mut WlSurface,
     id: i32,
     x: i32,
     y: i32,
 ) {
-    println!("Touch::Down {} {} {}", id, x, y);
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let wayland = connection.window(surface);
+    if wayland.is_null() {
+        return;
+    }
+    let (x, y) = unsafe { (*wayland).window_position(x, y) };
+
+    connection.serial = serial;
+    let input = connection.touches.down(wayland, id, x, y);
+    connection.touch_input(wayland, input);
 }
 
 extern "C" fn touch_handle_up(
-    _window: *mut c_void,
+    connection: *mut c_void,
     _touch: *mut WlTouch,
     _serial: u32,
     _time: u32,
     id: i32,
 ) {
-    println!("Touch::Up {}", id);
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+
+    if let Some((wayland, input)) = connection.touches.up(id) {
+        connection.touch_input(wayland, input);
+    }
 }
 
 extern "C" fn touch_handle_motion(
-    _window: *mut c_void,
+    connection: *mut c_void,
     _touch: *mut WlTouch,
     _time: u32,
     id: i32,
     x: i32,
     y: i32,
 ) {
-    println!("Touch::Motion {} {} {}", id, x, y);
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let position =
+        |wayland: *mut Wayland| unsafe { (*wayland).window_position(x, y) };
+
+    if let Some((wayland, input)) = connection.touches.motion(id, position) {
+        connection.touch_input(wayland, input);
+    }
+}
+
+extern "C" fn touch_handle_frame(
+    connection: *mut c_void,
+    _touch: *mut WlTouch,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+
+    // One event for each window, in the order they were touched.
+    for (wayland, touches) in connection.touches.frame() {
+        unsafe {
+            (*wayland)
+                .events
+                .push_back(crate::WindowEvent::Touch(touches));
+        }
+    }
+}
+
+extern "C" fn touch_handle_shape(
+    _connection: *mut c_void,
+    _touch: *mut WlTouch,
+    _id: i32,
+    _major: i32,
+    _minor: i32,
+) {
 }
 
-extern "C" fn touch_handle_frame(_data: *mut c_void, _touch: *mut WlTouch) {
-    println!("Touch::Frame");
+extern "C" fn touch_handle_orientation(
+    _connection: *mut c_void,
+    _touch: *mut WlTouch,
+    _id: i32,
+    _orientation: i32,
+) {
 }
 
-extern "C" fn touch_handle_cancel(_data: *mut c_void, _touch: *mut WlTouch) {
-    println!("Touch::Cancel");
+extern "C" fn touch_handle_cancel(
+    connection: *mut c_void,
+    _touch: *mut WlTouch,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let (windows, primary) = connection.touches.cancel();
+
+    if let Some(wayland) = primary {
+        connection.touch_input(wayland, vec![Input::Touch(false)]);
+    }
+    for wayland in windows {
+        unsafe {
+            (*wayland).events.push_back(crate::WindowEvent::TouchCancel);
+        }
+    }
 }
 
 extern "C" fn keyboard_handle_keymap(
-    _window: *mut c_void,
+    connection: *mut c_void,
     _keyboard: *mut WlKeyboard,
-    _format: u32,
-    _fd: i32,
-    _size: u32,
+    format: u32,
+    fd: i32,
+    size: u32,
 ) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+
+    // Only XKB v1 (1) keymaps are supported.
+    if format != 1 {
+        // The file descriptor is ours to close.
+        drop(unsafe { File::from_raw_fd(fd) });
+        return;
+    }
+    connection.keymap.set_keymap(fd, size.try_into().unwrap());
 }
 
 extern "C" fn keyboard_handle_enter(
-    _window: *mut c_void,
+    connection: *mut c_void,
     _keyboard: *mut WlKeyboard,
     _serial: u32,
-    _surface: *mut WlSurface,
-    _keys: *mut WlArray,
+    surface: *mut WlSurface,
+    keys: *mut WlArray,
 ) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let wayland = connection.window(surface);
+    if wayland.is_null() {
+        return;
+    }
+
+    connection.keyboard_focus = wayland;
+    unsafe {
+        (*wayland).events.push_back(crate::WindowEvent::FocusGained);
+    }
+    // Keys already held down are pressed as the window gets focus.
+    let keys: &[u32] = unsafe {
+        let keys = &*keys;
+        if keys.size == 0 {
+            &[]
+        } else {
+            std::slice::from_raw_parts(keys.data.cast(), keys.size / 4)
+        }
+    };
+    for key in keys.iter().cloned() {
+        connection.key_state(key, true);
+    }
 }
 
 extern "C" fn keyboard_handle_leave(
-    _window: *mut c_void,
+    connection: *mut c_void,
     _keyboard: *mut WlKeyboard,
     _serial: u32,
     _surface: *mut WlSurface,
 ) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let wayland = connection.keyboard_focus;
+
+    // Release held keys, since the window won't hear about it later.
+    for key in std::mem::take(&mut connection.held_keys) {
+        connection.key_state(key, false);
+    }
+    if !wayland.is_null() {
+        unsafe {
+            (*wayland).events.push_back(crate::WindowEvent::FocusLost);
+        }
+    }
+    connection.keyboard_focus = null_mut();
+    connection.repeat = None;
 }
 
 extern "C" fn keyboard_handle_key(
-    wayland: *mut c_void,
+    connection: *mut c_void,
     _keyboard: *mut WlKeyboard,
     _serial: u32,
     _time: u32,
     key: u32,
     state: u32,
 ) {
-    let window: &mut Wayland = unsafe { &mut *wayland.cast() };
-
-    if key == 87 /*KEY_F11*/ && state != 0 {
-        (*window).configured = true;
-
-        if (*window).fullscreen {
-            unsafe {
-                (*window)
-                    .client
-                    .zxdg_toplevel_v6_unset_fullscreen((*window).toplevel);
-            }
-            (*window).fullscreen = false;
-        } else {
-            unsafe {
-                (*window)
-                    .client
-                    .zxdg_toplevel_v6_set_fullscreen((*window).toplevel);
-            }
-            (*window).fullscreen = true;
-        }
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let wayland = connection.keyboard_focus;
+    if wayland.is_null() {
+        return;
+    }
+    let window: &mut Wayland = unsafe { &mut *wayland };
 
-        let callback = unsafe {
-            (*window).client.display_sync((*window).display.as_ptr())
-        };
+    // F11 presses toggle fullscreen when enabled, and neither presses nor
+    // releases are sent.
+    if key == 87 /*KEY_F11*/ && window.fullscreen_key {
+        use crate::Nwin;
 
-        unsafe {
-            (*window).client.callback_add_listener(
-                callback,
-                &FRAME_LISTENER,
-                wayland,
-            );
+        if state != 0 {
+            window.set_fullscreen(!window.status.fullscreen);
         }
     } else {
         let held = state != 0;
-        let mods = Mod::new();
-
-        window.input_queue.push(match key {
-            1 => Input::Key(mods, Key::Back, held),
-            2 => Input::Key(mods, Key::One, held),
-            3 => Input::Key(mods, Key::Two, held),
-            4 => Input::Key(mods, Key::Three, held),
-            5 => Input::Key(mods, Key::Four, held),
-            6 => Input::Key(mods, Key::Five, held),
-            7 => Input::Key(mods, Key::Six, held),
-            8 => Input::Key(mods, Key::Seven, held),
-            9 => Input::Key(mods, Key::Eight, held),
-            10 => Input::Key(mods, Key::Nine, held),
-            11 => Input::Key(mods, Key::Zero, held),
-            12 => Input::Key(mods, Key::Minus, held),
-            13 => Input::Key(mods, Key::Equal, held),
-            14 => Input::Key(mods, Key::Backspace, held),
-            15 => Input::Key(mods, Key::Tab, held),
-            16 => Input::Key(mods, Key::Q, held),
-            17 => Input::Key(mods, Key::W, held),
-            18 => Input::Key(mods, Key::E, held),
-            19 => Input::Key(mods, Key::R, held),
-            20 => Input::Key(mods, Key::T, held),
-            21 => Input::Key(mods, Key::Y, held),
-            22 => Input::Key(mods, Key::U, held),
-            23 => Input::Key(mods, Key::I, held),
-            24 => Input::Key(mods, Key::O, held),
-            25 => Input::Key(mods, Key::P, held),
-            26 => Input::Key(mods, Key::BracketOpen, held),
-            27 => Input::Key(mods, Key::BracketClose, held),
-            28 => Input::Key(mods, Key::Enter, held),
-            29 => Input::Key(mods, Key::LCtrl, held),
-            30 => Input::Key(mods, Key::A, held),
-            31 => Input::Key(mods, Key::S, held),
-            32 => Input::Key(mods, Key::D, held),
-            33 => Input::Key(mods, Key::F, held),
-            34 => Input::Key(mods, Key::G, held),
-            35 => Input::Key(mods, Key::H, held),
-            36 => Input::Key(mods, Key::J, held),
-            37 => Input::Key(mods, Key::K, held),
-            38 => Input::Key(mods, Key::L, held),
-            39 => Input::Key(mods, Key::Semicolon, held),
-            40 => Input::Key(mods, Key::Apostrophe, held),
-            41 => Input::Key(mods, Key::Backtick, held),
-            42 => Input::Key(mods, Key::LShift, held),
-            43 => Input::Key(mods, Key::Backslash, held),
-            44 => Input::Key(mods, Key::Z, held),
-            45 => Input::Key(mods, Key::X, held),
-            46 => Input::Key(mods, Key::C, held),
-            47 => Input::Key(mods, Key::V, held),
-            48 => Input::Key(mods, Key::B, held),
-            49 => Input::Key(mods, Key::N, held),
-            50 => Input::Key(mods, Key::M, held),
-            51 => Input::Key(mods, Key::Comma, held),
-            52 => Input::Key(mods, Key::Period, held),
-            53 => Input::Key(mods, Key::Slash, held),
-            54 => Input::Key(mods, Key::RShift, held),
-            55 => Input::Key(mods.add_shift(), Key::Eight, held), // NumMul
-            56 => Input::Key(mods, Key::LAlt, held),
-            57 => Input::Key(mods, Key::Space, held),
-            58 => Input::Key(mods, Key::CapsLock, held),
-            59 => Input::Key(mods, Key::F1, held),
-            60 => Input::Key(mods, Key::F2, held),
-            61 => Input::Key(mods, Key::F3, held),
-            62 => Input::Key(mods, Key::F4, held),
-            63 => Input::Key(mods, Key::F5, held),
-            64 => Input::Key(mods, Key::F6, held),
-            65 => Input::Key(mods, Key::F7, held),
-            66 => Input::Key(mods, Key::F8, held),
-            67 => Input::Key(mods, Key::F9, held),
-            68 => Input::Key(mods, Key::F10, held),
-            69 => Input::Key(mods, Key::Compose, held), // NumpadLock,
-            70 => Input::Key(mods, Key::Compose, held), // ScrollLock,
-            71 => Input::Key(mods, Key::Seven, held),   // Numpad7,
-            72 => Input::Key(mods, Key::Eight, held),   // Numpad8,
-            73 => Input::Key(mods, Key::Nine, held),    // Numpad9,
-            74 => Input::Key(mods, Key::Minus, held),   // NumpadSubtract,
-            75 => Input::Key(mods, Key::Four, held),    // Numpad4,
-            76 => Input::Key(mods, Key::Five, held),    // Numpad5,
-            77 => Input::Key(mods, Key::Six, held),     // Numpad6,
-            78 => Input::Key(mods.add_shift(), Key::Equal, held), // NumAdd,
-            79 => Input::Key(mods, Key::One, held),     // Numpad1,
-            80 => Input::Key(mods, Key::Two, held),     // Numpad2,
-            81 => Input::Key(mods, Key::Three, held),   // Numpad3,
-            82 => Input::Key(mods, Key::Zero, held),    // Numpad0,
-            83 => Input::Key(mods, Key::Period, held),  // NumpadDot,
-            84 => Input::Key(mods, Key::Compose, held), // RESERVED
-            85 => Input::Key(mods, Key::Compose, held), // KEY_ZENKAKUHANKAKU
-            86 => Input::Key(mods, Key::Compose, held), // KEY_102ND
-            87 => Input::Key(mods, Key::F11, held),
-            88 => Input::Key(mods, Key::F12, held),
-            89 => Input::Key(mods, Key::Compose, held), // KEY_RO
-            90 => Input::Key(mods, Key::Compose, held), // KEY_KATAKANA
-            91 => Input::Key(mods, Key::Compose, held), // KEY_HIRAGANA
-            92 => Input::Key(mods, Key::Compose, held), // KEY_HENKAN
-            93 => Input::Key(mods, Key::Compose, held), // KEY_KATAKANAHIRAGANA
-            94 => Input::Key(mods, Key::Compose, held), // KEY_MUHENKAN
-            95 => Input::Key(mods, Key::Compose, held), // KEY_KPJPCOMMA
-            96 => Input::Key(mods, Key::Enter, held),   // NumpadEnter,
-            97 => Input::Key(mods, Key::RCtrl, held),   // RightCtrl,
-            98 => Input::Key(mods, Key::Slash, held),   // NumpadDivide,
-            99 => return,                               // PrintScreen,
-            100 => Input::Key(mods, Key::RAlt, held),
-            101 => Input::Key(mods, Key::Enter, held), // KEY_LINEFEED
-            102 => Input::Key(mods, Key::Home, held),
-            103 => Input::Key(mods, Key::Up, held),
-            104 => Input::Key(mods, Key::PageUp, held),
-            105 => Input::Key(mods, Key::Left, held),
-            106 => Input::Key(mods, Key::Right, held),
-            107 => Input::Key(mods, Key::End, held),
-            108 => Input::Key(mods, Key::Down, held),
-            109 => Input::Key(mods, Key::PageDown, held),
-            110 => Input::Key(mods, Key::Insert, held),
-            111 => Input::Key(mods, Key::Delete, held),
-            112 => Input::Key(mods, Key::Compose, held), // KEY_MACRO
-            113 => return,                               // Mute,
-            114 => return,                               // VolumeDown,
-            115 => return,                               // VolumeUp,
-            116 => return,                               // KEY_POWER
-            117 => return,                               // KEY_KPEQUAL
-            118 => return,                               // KEY_KPPLUSMINUS
-            119 => return,                               // Break,
-            120 => return,                               // KEY_SCALE
-            121 => return,                               // KEY_KPCOMMA
-            122 => return,                               // KEY_HANGEUL
-            123 => return,                               // KEY_HANJA
-            124 => return,                               // KEY_YEN
-            125 => Input::Key(mods, Key::LCtrl, held),   // Left Meta
-            126 => Input::Key(mods, Key::RCtrl, held),   // Right Meta
-            127 => Input::Key(mods, Key::Compose, held),
-            // Extra Keys
-            e => {
-                eprintln!("Error: Unknown key: {}", e);
-                return;
-            }
-        });
+
+        connection.key_input(key, held);
+        if held && connection.keymap.repeats(key) {
+            let delay = Duration::from_millis(connection.repeat_delay as u64);
+            connection.repeat = Some((key, Instant::now() + delay));
+        } else if connection.repeat.map(|(k, _)| k) == Some(key) {
+            connection.repeat = None;
+        }
+    }
+}
+
+extern "C" fn keyboard_handle_repeat_info(
+    connection: *mut c_void,
+    _keyboard: *mut WlKeyboard,
+    rate: i32,
+    delay: i32,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+
+    connection.repeat_rate = rate.max(0);
+    connection.repeat_delay = delay.max(0);
+    if rate <= 0 {
+        connection.repeat = None;
     }
 }
 
+// Get the input for an evdev key code on a US QWERTY keyboard.
+fn evdev_key(key: u32, mods: Mod, held: bool) -> Option<Input> {
+    Some(match key {
+        1 => Input::Key(mods, Key::Back, held),
+        2 => Input::Key(mods, Key::One, held),
+        3 => Input::Key(mods, Key::Two, held),
+        4 => Input::Key(mods, Key::Three, held),
+        5 => Input::Key(mods, Key::Four, held),
+        6 => Input::Key(mods, Key::Five, held),
+        7 => Input::Key(mods, Key::Six, held),
+        8 => Input::Key(mods, Key::Seven, held),
+        9 => Input::Key(mods, Key::Eight, held),
+        10 => Input::Key(mods, Key::Nine, held),
+        11 => Input::Key(mods, Key::Zero, held),
+        12 => Input::Key(mods, Key::Minus, held),
+        13 => Input::Key(mods, Key::Equal, held),
+        14 => Input::Key(mods, Key::Backspace, held),
+        15 => Input::Key(mods, Key::Tab, held),
+        16 => Input::Key(mods, Key::Q, held),
+        17 => Input::Key(mods, Key::W, held),
+        18 => Input::Key(mods, Key::E, held),
+        19 => Input::Key(mods, Key::R, held),
+        20 => Input::Key(mods, Key::T, held),
+        21 => Input::Key(mods, Key::Y, held),
+        22 => Input::Key(mods, Key::U, held),
+        23 => Input::Key(mods, Key::I, held),
+        24 => Input::Key(mods, Key::O, held),
+        25 => Input::Key(mods, Key::P, held),
+        26 => Input::Key(mods, Key::BracketOpen, held),
+        27 => Input::Key(mods, Key::BracketClose, held),
+        28 => Input::Key(mods, Key::Enter, held),
+        29 => Input::Key(mods, Key::LCtrl, held),
+        30 => Input::Key(mods, Key::A, held),
+        31 => Input::Key(mods, Key::S, held),
+        32 => Input::Key(mods, Key::D, held),
+        33 => Input::Key(mods, Key::F, held),
+        34 => Input::Key(mods, Key::G, held),
+        35 => Input::Key(mods, Key::H, held),
+        36 => Input::Key(mods, Key::J, held),
+        37 => Input::Key(mods, Key::K, held),
+        38 => Input::Key(mods, Key::L, held),
+        39 => Input::Key(mods, Key::Semicolon, held),
+        40 => Input::Key(mods, Key::Apostrophe, held),
+        41 => Input::Key(mods, Key::Backtick, held),
+        42 => Input::Key(mods, Key::LShift, held),
+        43 => Input::Key(mods, Key::Backslash, held),
+        44 => Input::Key(mods, Key::Z, held),
+        45 => Input::Key(mods, Key::X, held),
+        46 => Input::Key(mods, Key::C, held),
+        47 => Input::Key(mods, Key::V, held),
+        48 => Input::Key(mods, Key::B, held),
+        49 => Input::Key(mods, Key::N, held),
+        50 => Input::Key(mods, Key::M, held),
+        51 => Input::Key(mods, Key::Comma, held),
+        52 => Input::Key(mods, Key::Period, held),
+        53 => Input::Key(mods, Key::Slash, held),
+        54 => Input::Key(mods, Key::RShift, held),
+        55 => Input::Key(mods.add_shift(), Key::Eight, held), // NumMul
+        56 => Input::Key(mods, Key::LAlt, held),
+        57 => Input::Key(mods, Key::Space, held),
+        58 => Input::Key(mods, Key::CapsLock, held),
+        59 => Input::Key(mods, Key::F1, held),
+        60 => Input::Key(mods, Key::F2, held),
+        61 => Input::Key(mods, Key::F3, held),
+        62 => Input::Key(mods, Key::F4, held),
+        63 => Input::Key(mods, Key::F5, held),
+        64 => Input::Key(mods, Key::F6, held),
+        65 => Input::Key(mods, Key::F7, held),
+        66 => Input::Key(mods, Key::F8, held),
+        67 => Input::Key(mods, Key::F9, held),
+        68 => Input::Key(mods, Key::F10, held),
+        69 => Input::Key(mods, Key::Compose, held), // NumpadLock,
+        70 => Input::Key(mods, Key::Compose, held), // ScrollLock,
+        71 => Input::Key(mods, Key::Seven, held),   // Numpad7,
+        72 => Input::Key(mods, Key::Eight, held),   // Numpad8,
+        73 => Input::Key(mods, Key::Nine, held),    // Numpad9,
+        74 => Input::Key(mods, Key::Minus, held),   // NumpadSubtract,
+        75 => Input::Key(mods, Key::Four, held),    // Numpad4,
+        76 => Input::Key(mods, Key::Five, held),    // Numpad5,
+        77 => Input::Key(mods, Key::Six, held),     // Numpad6,
+        78 => Input::Key(mods.add_shift(), Key::Equal, held), // NumAdd,
+        79 => Input::Key(mods, Key::One, held),     // Numpad1,
+        80 => Input::Key(mods, Key::Two, held),     // Numpad2,
+        81 => Input::Key(mods, Key::Three, held),   // Numpad3,
+        82 => Input::Key(mods, Key::Zero, held),    // Numpad0,
+        83 => Input::Key(mods, Key::Period, held),  // NumpadDot,
+        84 => Input::Key(mods, Key::Compose, held), // RESERVED
+        85 => Input::Key(mods, Key::Compose, held), // KEY_ZENKAKUHANKAKU
+        86 => Input::Key(mods, Key::Compose, held), // KEY_102ND
+        87 => Input::Key(mods, Key::F11, held),
+        88 => Input::Key(mods, Key::F12, held),
+        89 => Input::Key(mods, Key::Compose, held), // KEY_RO
+        90 => Input::Key(mods, Key::Compose, held), // KEY_KATAKANA
+        91 => Input::Key(mods, Key::Compose, held), // KEY_HIRAGANA
+        92 => Input::Key(mods, Key::Compose, held), // KEY_HENKAN
+        93 => Input::Key(mods, Key::Compose, held), // KEY_KATAKANAHIRAGANA
+        94 => Input::Key(mods, Key::Compose, held), // KEY_MUHENKAN
+        95 => Input::Key(mods, Key::Compose, held), // KEY_KPJPCOMMA
+        96 => Input::Key(mods, Key::Enter, held),   // NumpadEnter,
+        97 => Input::Key(mods, Key::RCtrl, held),   // RightCtrl,
+        98 => Input::Key(mods, Key::Slash, held),   // NumpadDivide,
+        99 => return None,                          // PrintScreen,
+        100 => Input::Key(mods, Key::RAlt, held),
+        101 => Input::Key(mods, Key::Enter, held), // KEY_LINEFEED
+        102 => Input::Key(mods, Key::Home, held),
+        103 => Input::Key(mods, Key::Up, held),
+        104 => Input::Key(mods, Key::PageUp, held),
+        105 => Input::Key(mods, Key::Left, held),
+        106 => Input::Key(mods, Key::Right, held),
+        107 => Input::Key(mods, Key::End, held),
+        108 => Input::Key(mods, Key::Down, held),
+        109 => Input::Key(mods, Key::PageDown, held),
+        110 => Input::Key(mods, Key::Insert, held),
+        111 => Input::Key(mods, Key::Delete, held),
+        112 => Input::Key(mods, Key::Compose, held), // KEY_MACRO
+        113 => return None,                          // Mute,
+        114 => return None,                          // VolumeDown,
+        115 => return None,                          // VolumeUp,
+        116 => return None,                          // KEY_POWER
+        117 => return None,                          // KEY_KPEQUAL
+        118 => return None,                          // KEY_KPPLUSMINUS
+        119 => return None,                          // Break,
+        120 => return None,                          // KEY_SCALE
+        121 => return None,                          // KEY_KPCOMMA
+        122 => return None,                          // KEY_HANGEUL
+        123 => return None,                          // KEY_HANJA
+        124 => return None,                          // KEY_YEN
+        125 => Input::Key(mods, Key::LCtrl, held),   // Left Meta
+        126 => Input::Key(mods, Key::RCtrl, held),   // Right Meta
+        127 => Input::Key(mods, Key::Compose, held),
+        // Extra Keys
+        e => {
+            eprintln!("Error: Unknown key: {}", e);
+            return None;
+        }
+    })
+}
+
 extern "C" fn keyboard_handle_modifiers(
-    _window: *mut c_void,
+    connection: *mut c_void,
     _keyboard: *mut WlKeyboard,
     _serial: u32,
-    _mods_depressed: u32,
-    _mods_latched: u32,
-    _mods_locked: u32,
-    _group: u32,
+    mods_depressed: u32,
+    mods_latched: u32,
+    mods_locked: u32,
+    group: u32,
 ) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+
+    connection
+        .keymap
+        .update(mods_depressed, mods_latched, mods_locked, group);
 }
 
 extern "C" fn pointer_handle_enter(
-    window: *mut c_void,
+    connection: *mut c_void,
     pointer: *mut WlPointer,
     serial: u32,
-    _surface: *mut WlSurface,
-    _sx: i32,
-    _sy: i32,
+    surface: *mut WlSurface,
+    sx: i32,
+    sy: i32,
 ) {
     unsafe {
-        let window: *mut Wayland = window.cast();
+        let connection: *mut Connection = connection.cast();
+
+        (*connection).pointer_focus = (*connection).window(surface);
+        if !(*connection).pointer_focus.is_null() {
+            (*(*connection).pointer_focus)
+                .events
+                .push_back(crate::WindowEvent::PointerEntered);
+            pointer_motion(&mut *connection, 0, sx, sy);
+        }
 
-        let cursor = (*window).default_cursor;
+        let cursor = (*connection).default_cursor;
+        if cursor.is_null() {
+            return;
+        }
         let image = *(*cursor).images;
-        let buffer = ((*window).cursor.wl_cursor_image_get_buffer)(image);
+        let buffer = ((*connection).cursor.wl_cursor_image_get_buffer)(image);
         if buffer.is_null() {
             return;
         }
 
-        (*window).client.pointer_set_cursor(
+        (*connection).client.pointer_set_cursor(
             pointer,
-            (*window).cursor_surface,
+            (*connection).cursor_surface,
             image,
             serial,
         );
-        (*window)
+        (*connection)
             .client
-            .surface_attach((*window).cursor_surface, buffer);
-        (*window)
+            .surface_attach((*connection).cursor_surface, buffer);
+        (*connection)
             .client
-            .surface_damage((*window).cursor_surface, image);
-        (*window).client.surface_commit((*window).cursor_surface);
+            .surface_damage((*connection).cursor_surface, image);
+        (*connection)
+            .client
+            .surface_commit((*connection).cursor_surface);
     }
 }
 
 extern "C" fn pointer_handle_leave(
-    _window: *mut c_void,
+    connection: *mut c_void,
     _pointer: *mut WlPointer,
     _serial: u32,
     _surface: *mut WlSurface,
 ) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let wayland = connection.pointer_focus;
+    if wayland.is_null() {
+        return;
+    }
+
+    connection.pointer_input(Input::PointerLeave);
+    connection.pointer_frame();
+    unsafe {
+        (*wayland).frame_hit = None;
+        (*wayland).events.push_back(crate::WindowEvent::PointerLeft);
+    }
+    connection.pointer_focus = null_mut();
 }
 
 extern "C" fn pointer_handle_motion(
-    window: *mut c_void,
+    connection: *mut c_void,
     _pointer: *mut WlPointer,
-    _time: u32,
+    time: u32,
     x: i32,
     y: i32,
 ) {
-    let wayland: &mut Wayland = unsafe { &mut *window.cast() };
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    if connection.pointer_focus.is_null() {
+        return;
+    }
 
-    let w = (wayland.window_width as f32 * 256.0).recip();
+    pointer_motion(connection, time, x, y);
+}
 
-    let x = x as f32 * w;
-    let y = y as f32 * w;
+// Move the pointer to a new position (24.8 fixed point surface coordinates)
+// on the window that has pointer focus.
+fn pointer_motion(connection: &mut Connection, time: u32, x: i32, y: i32) {
+    let wayland: &mut Wayland = unsafe { &mut *connection.pointer_focus };
+    connection.pointer_position = (x / 256, y / 256);
+
+    // Pointer over the built-in frame isn't reported to the window.
+    wayland.frame_hit = None;
+    if wayland.framed() {
+        let (width, height) = wayland.surface_size();
+        let size = (width.try_into().unwrap(), height.try_into().unwrap());
+        let hit = crate::frame::hit(size, x / 256, y / 256);
+        if hit != crate::frame::FrameHit::Content {
+            wayland.frame_hit = Some(hit);
+            return;
+        }
+    }
+    let (left, top, _right, _bottom) = wayland.margins();
+    let logical_x = (x - left * 256) as f32 / 256.0;
+    let logical_y = (y - top * 256) as f32 / 256.0;
+    let (x, y) = wayland.window_position(x, y);
 
-    wayland.move_state = (wayland.move_)(x, y);
+    if let Some(ref mut hit_test) = wayland.hit_test {
+        wayland.region = hit_test(x, y);
+    }
 
-    wayland.input_queue.push(Input::PointerX(x));
-    wayland.input_queue.push(Input::PointerY(y));
+    connection.pointer_input(Input::PointerX(x));
+    connection.pointer_input(Input::PointerY(y));
+    connection.pointer_motion = Some(crate::PointerMotion {
+        x,
+        y,
+        logical_x,
+        logical_y,
+        time,
+    });
 }
 
 extern "C" fn pointer_handle_button(
-    window: *mut c_void,
+    connection: *mut c_void,
     _pointer: *mut WlPointer,
     serial: u32,
     _time: u32,
     button: u32,
     state: u32,
 ) {
-    let window: &mut Wayland = unsafe { &mut *window.cast() };
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    if connection.pointer_focus.is_null() {
+        return;
+    }
+    let window: &mut Wayland = unsafe { &mut *connection.pointer_focus };
     let pressed = state != 0;
+    if pressed {
+        connection.serial = serial;
+    }
 
     // FIXME: which is which? DPI,EXTRA,TASK,SIDE
-    let mods = Mod::new();
+    let mods = connection.keymap.mods();
     let mut input = match button {
         0x110 /*BTN_LEFT*/ => Some(Input::Click(mods, Btn::Left, pressed)),
         0x111 /*BTN_RIGHT*/ => Some(Input::Click(mods, Btn::Right, pressed)),
@@ -2092,40 +5189,202 @@ extern "C" fn pointer_handle_button(
         0x117 /*BTN_TASK*/ => Some(Input::Click(mods, Btn::Extra, pressed)),
         _ => None,
     };
+    if let Some(frame_hit) = window.frame_hit {
+        frame_button(connection, window, frame_hit, button, pressed, serial);
+        // Releases still go to the window, in case it saw the press.
+        if pressed {
+            return;
+        }
+    }
     if let Some(input) = input.take() {
+        // Let the window manager handle drags on title bars and edges.
         if matches!(input, Input::Click(_mods, Btn::Left, true)) {
+            let client = &connection.client;
+            match window.region {
+                crate::Region::Client => {}
+                crate::Region::TitleBar => {
+                    unsafe {
+                        client.zxdg_toplevel_v6_move(
+                            window.toplevel,
+                            connection.seat,
+                            serial,
+                        );
+                    }
+                    return;
+                }
+                crate::Region::Edge(edge) => {
+                    unsafe {
+                        client.zxdg_toplevel_v6_resize(
+                            window.toplevel,
+                            connection.seat,
+                            serial,
+                            edge,
+                        );
+                    }
+                    return;
+                }
+            }
+        }
+        connection.pointer_input(input);
+    }
+}
+
+// Handle a button press or release on the built-in frame.
+fn frame_button(
+    connection: &Connection,
+    window: &mut Wayland,
+    hit: crate::frame::FrameHit,
+    button: u32,
+    pressed: bool,
+    serial: u32,
+) {
+    use crate::frame::{FrameButton, FrameHit};
+
+    let client = &connection.client;
+    match (hit, button, pressed) {
+        (FrameHit::TitleBar, 0x110 /*BTN_LEFT*/, true) => unsafe {
+            client.zxdg_toplevel_v6_move(
+                window.toplevel,
+                connection.seat,
+                serial,
+            );
+        },
+        (FrameHit::Edge(edge), 0x110 /*BTN_LEFT*/, true) => unsafe {
+            client.zxdg_toplevel_v6_resize(
+                window.toplevel,
+                connection.seat,
+                serial,
+                edge,
+            );
+        },
+        (FrameHit::TitleBar, 0x111 /*BTN_RIGHT*/, true) => {
+            let (x, y) = connection.pointer_position;
             unsafe {
-                (window.client.wl_proxy_marshal)(
-                    window.toplevel.cast(),
-                    5, /*ZXDG_TOPLEVEL_V6_MOVE*/
-                    window.seat,
+                client.zxdg_toplevel_v6_show_window_menu(
+                    window.toplevel,
+                    connection.seat,
                     serial,
+                    x,
+                    y,
                 );
             }
         }
-        window.input_queue.push(input);
+        (FrameHit::Button(button), 0x110 /*BTN_LEFT*/, false) => match button {
+            FrameButton::Close => {
+                window.events.push_back(crate::WindowEvent::CloseRequested);
+                if window.close_on_request {
+                    window.closed = true;
+                }
+            }
+            FrameButton::Maximize => {
+                let maximized = window.status.maximized;
+                crate::Nwin::set_maximized(window, !maximized);
+            }
+            FrameButton::Minimize => crate::Nwin::minimize(window),
+        },
+        _ => {}
     }
 }
 
 extern "C" fn pointer_handle_axis(
-    window: *mut c_void,
+    connection: *mut c_void,
     _pointer: *mut WlPointer,
-    _time: u32,
+    time: u32,
     axis: u32,
     value: i32,
 ) {
-    let window: &mut Wayland = unsafe { &mut *window.cast() };
-
-    window.input_queue.push(match axis {
-        // FIXME: Mods should reflect modifiers that are held down (for whole
-        // file not just here).
-        0 => Input::ScrollY(Mod::new(), value as f32 / 2560.0),
-        1 => Input::ScrollX(Mod::new(), value as f32 / 2560.0),
-        x => {
-            eprintln!("Unknown Wayland Axis {}", x);
-            return;
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    if connection.pointer_focus.is_null() {
+        return;
+    }
+    // Logical pixels; a mouse wheel notch is usually 10.
+    let value = value as f32 / 256.0;
+
+    let mods = connection.keymap.mods();
+    let scroll = connection.scroll();
+    scroll.time = time;
+    let input = match axis {
+        0 /*VERTICAL_SCROLL*/ => {
+            scroll.y += value;
+            Input::ScrollY(mods, value / 10.0)
         }
-    });
+        1 /*HORIZONTAL_SCROLL*/ => {
+            scroll.x += value;
+            Input::ScrollX(mods, value / 10.0)
+        }
+        _ => return,
+    };
+    connection.pointer_input(input);
+}
+
+extern "C" fn pointer_handle_frame(
+    connection: *mut c_void,
+    _pointer: *mut WlPointer,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+
+    connection.pointer_frame();
+}
+
+extern "C" fn pointer_handle_axis_source(
+    connection: *mut c_void,
+    _pointer: *mut WlPointer,
+    axis_source: u32,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+
+    connection.scroll().source = match axis_source {
+        0 => crate::ScrollSource::Wheel,
+        1 => crate::ScrollSource::Finger,
+        2 => crate::ScrollSource::Continuous,
+        3 => crate::ScrollSource::WheelTilt,
+        _ => crate::ScrollSource::Unknown,
+    };
+}
+
+extern "C" fn pointer_handle_axis_stop(
+    connection: *mut c_void,
+    _pointer: *mut WlPointer,
+    time: u32,
+    axis: u32,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let scroll = connection.scroll();
+
+    scroll.time = time;
+    match axis {
+        0 => scroll.stop_y = true,
+        1 => scroll.stop_x = true,
+        _ => {}
+    }
+}
+
+// Only sent before version 8.
+extern "C" fn pointer_handle_axis_discrete(
+    connection: *mut c_void,
+    pointer: *mut WlPointer,
+    axis: u32,
+    discrete: i32,
+) {
+    pointer_handle_axis_value120(connection, pointer, axis, discrete * 120);
+}
+
+// Only sent since version 8.
+extern "C" fn pointer_handle_axis_value120(
+    connection: *mut c_void,
+    _pointer: *mut WlPointer,
+    axis: u32,
+    value120: i32,
+) {
+    let connection: &mut Connection = unsafe { &mut *connection.cast() };
+    let scroll = connection.scroll();
+    let steps = value120 as f32 / 120.0;
+
+    match axis {
+        0 => scroll.steps_y += steps,
+        1 => scroll.steps_x += steps,
+        _ => {}
+    }
 }
 
 extern "C" fn redraw_wl(
@@ -2134,28 +5393,63 @@ extern "C" fn redraw_wl(
     _millis: u32, // Use refresh rate instead
 ) {
     let wayland: &mut Wayland = unsafe { &mut *data.cast() };
+    let connection = wayland.connection;
 
     unsafe {
+        let client = &(*connection).client;
         if !callback.is_null() {
-            wayland.client.callback_destroy(callback);
+            client.callback_destroy(callback);
         }
         wayland.callback = std::ptr::null_mut();
 
-        // Start rendering on the screen.
-        (*wayland.draw.unwrap().as_ptr()).begin_draw();
+        // Another window is running (or none is), so draw the next time this
+        // one runs.
+        if wayland.window.is_null() {
+            wayland.frame_pending = true;
+            return;
+        }
 
-        // Draw user-defined objects.
-        (wayland.redraw)(&mut *wayland.window, wayland.refresh_rate);
+        wayland.draw_frame();
+    }
+}
 
-        // Get ready for next frame.
-        wayland.callback = wayland.client.surface_frame((*wayland).surface);
-        wayland.client.callback_add_listener(
-            wayland.callback,
-            &FRAME_LISTENER,
-            data,
+#[cfg(test)]
+mod tests {
+    use super::decode_states;
+    use crate::WindowStatus;
+
+    #[test]
+    fn no_states() {
+        assert_eq!(decode_states(&[]), WindowStatus::default());
+    }
+
+    #[test]
+    fn each_state() {
+        let status = decode_states(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(
+            status,
+            WindowStatus {
+                maximized: true,
+                fullscreen: true,
+                resizing: true,
+                activated: true,
+                tiled_left: true,
+                tiled_right: true,
+                tiled_top: true,
+                tiled_bottom: true,
+            }
         );
+    }
 
-        // Finish rendering on the screen.
-        (*wayland.draw.unwrap().as_ptr()).finish_draw();
+    #[test]
+    fn unknown_and_repeated_states() {
+        let status = decode_states(&[0, 4, 9, 4, 1000]);
+        assert_eq!(
+            status,
+            WindowStatus {
+                activated: true,
+                ..WindowStatus::default()
+            }
+        );
     }
 }
@@ -0,0 +1,262 @@
+// Window
+// Copyright © 2019-2021 Jeron Aldaron Lau.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+//! Touch points, grouped into touch frames for each window.
+
+use human::Input;
+
+use crate::{Touch, TouchPhase};
+
+/// Touch points on windows (of type `W`).
+pub(super) struct Touches<W> {
+    // Touch points that are down, with the window they're on & position.
+    points: Vec<(i32, W, f32, f32)>,
+    // The touch point that's also reported as `Input::Touch`.
+    primary: Option<i32>,
+    // Changes waiting for the touch frame.
+    frame: Vec<(W, Touch)>,
+}
+
+impl<W: Copy + PartialEq> Touches<W> {
+    pub(super) fn new() -> Self {
+        Touches {
+            points: Vec::new(),
+            primary: None,
+            frame: Vec::new(),
+        }
+    }
+
+    /// A touch point went down on a window.  Returns the input to send to
+    /// the window (for the first finger down).
+    pub(super) fn down(
+        &mut self,
+        window: W,
+        id: i32,
+        x: f32,
+        y: f32,
+    ) -> Vec<Input> {
+        self.points.push((id, window, x, y));
+        self.change(window, id, TouchPhase::Down, x, y)
+    }
+
+    /// A touch point moved, to the position returned by `position` for the
+    /// window it's on.  Returns the window and the input to send to it.
+    pub(super) fn motion(
+        &mut self,
+        id: i32,
+        position: impl FnOnce(W) -> (f32, f32),
+    ) -> Option<(W, Vec<Input>)> {
+        let point = self.points.iter_mut().find(|p| p.0 == id)?;
+        let window = point.1;
+        let (x, y) = position(window);
+        point.2 = x;
+        point.3 = y;
+
+        let input = self.change(window, id, TouchPhase::Move, x, y);
+        Some((window, input))
+    }
+
+    /// A touch point went up.  Returns the window it was on and the input to
+    /// send to it.
+    pub(super) fn up(&mut self, id: i32) -> Option<(W, Vec<Input>)> {
+        let index = self.points.iter().position(|p| p.0 == id)?;
+        let (_, window, x, y) = self.points.remove(index);
+
+        let input = self.change(window, id, TouchPhase::Up, x, y);
+        Some((window, input))
+    }
+
+    /// End the touch frame: the changes since the last frame, grouped by
+    /// window in the order they were touched.
+    pub(super) fn frame(&mut self) -> Vec<(W, Vec<Touch>)> {
+        let mut frame = std::mem::take(&mut self.frame);
+        let mut windows = Vec::new();
+
+        while let Some((window, _)) = frame.first().cloned() {
+            let (touches, rest) = frame
+                .into_iter()
+                .partition::<Vec<_>, _>(|(w, _)| *w == window);
+            frame = rest;
+            let touches = touches.into_iter().map(|(_, touch)| touch).collect();
+            windows.push((window, touches));
+        }
+        windows
+    }
+
+    /// Forget all touch points.  Returns the windows they were on, and the
+    /// window of the first finger down (which needs an `Input::Touch`
+    /// release).
+    pub(super) fn cancel(&mut self) -> (Vec<W>, Option<W>) {
+        let primary = self.primary.take();
+        let mut windows = Vec::new();
+        let mut primary_window = None;
+
+        for (id, window, _, _) in std::mem::take(&mut self.points) {
+            if Some(id) == primary {
+                primary_window = Some(window);
+            }
+            if !windows.contains(&window) {
+                windows.push(window);
+            }
+        }
+        self.frame.clear();
+        (windows, primary_window)
+    }
+
+    /// Forget the touch points on a window that's closing.
+    pub(super) fn remove_window(&mut self, window: W) {
+        let primary = self.primary;
+        if self
+            .points
+            .iter()
+            .any(|p| Some(p.0) == primary && p.1 == window)
+        {
+            self.primary = None;
+        }
+        self.points.retain(|p| p.1 != window);
+        self.frame.retain(|(w, _)| *w != window);
+    }
+
+    // Queue a touch point change for the touch frame, and get the input for
+    // it if it's the first finger down (which is also reported like a
+    // pointer).
+    fn change(
+        &mut self,
+        window: W,
+        id: i32,
+        phase: TouchPhase,
+        x: f32,
+        y: f32,
+    ) -> Vec<Input> {
+        let touch = Touch { id, phase, x, y };
+        let mut input = Vec::new();
+
+        if touch.phase == TouchPhase::Down && self.primary.is_none() {
+            self.primary = Some(touch.id);
+        }
+        if self.primary == Some(touch.id) {
+            if touch.phase != TouchPhase::Up {
+                input.push(Input::PointerX(touch.x));
+                input.push(Input::PointerY(touch.y));
+            }
+            match touch.phase {
+                TouchPhase::Down => input.push(Input::Touch(true)),
+                TouchPhase::Move => {}
+                TouchPhase::Up => {
+                    input.push(Input::Touch(false));
+                    self.primary = None;
+                }
+            }
+        }
+        self.frame.push((window, touch));
+        input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use human::Input;
+
+    use super::Touches;
+    use crate::{Touch, TouchPhase, TouchPhase::*};
+
+    fn touch(id: i32, phase: TouchPhase, x: f32, y: f32) -> Touch {
+        Touch { id, phase, x, y }
+    }
+
+    #[test]
+    fn ids_survive_a_frame() {
+        let mut touches = Touches::new();
+
+        touches.down(1, 7, 10.0, 20.0);
+        touches.down(1, 3, 30.0, 40.0);
+        assert!(touches.motion(7, |_| (11.0, 21.0)).is_some());
+        assert!(touches.up(3).is_some());
+        assert_eq!(
+            touches.frame(),
+            vec![(
+                1,
+                vec![
+                    touch(7, Down, 10.0, 20.0),
+                    touch(3, Down, 30.0, 40.0),
+                    touch(7, Move, 11.0, 21.0),
+                    touch(3, Up, 30.0, 40.0),
+                ]
+            )]
+        );
+        // The frame is only sent once, and released points are forgotten.
+        assert!(touches.frame().is_empty());
+        assert!(touches.up(3).is_none());
+        assert_eq!(touches.up(7).map(|(window, _)| window), Some(1));
+    }
+
+    #[test]
+    fn frame_grouped_by_window() {
+        let mut touches = Touches::new();
+
+        touches.down(1, 0, 0.0, 0.0);
+        touches.down(2, 1, 5.0, 5.0);
+        touches.motion(0, |window| (window as f32, 1.0));
+        assert_eq!(
+            touches.frame(),
+            vec![
+                (1, vec![touch(0, Down, 0.0, 0.0), touch(0, Move, 1.0, 1.0)]),
+                (2, vec![touch(1, Down, 5.0, 5.0)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn first_finger_is_primary() {
+        let mut touches = Touches::new();
+
+        let input = touches.down(1, 4, 2.0, 3.0);
+        assert!(matches!(
+            input[..],
+            [Input::PointerX(_), Input::PointerY(_), Input::Touch(true)]
+        ));
+        assert!(touches.down(1, 5, 0.0, 0.0).is_empty());
+        let (_, input) = touches.up(4).unwrap();
+        assert!(matches!(input[..], [Input::Touch(false)]));
+        let (_, input) = touches.up(5).unwrap();
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn cancel_clears_points() {
+        let mut touches = Touches::new();
+
+        touches.down(1, 0, 0.0, 0.0);
+        touches.down(2, 1, 0.0, 0.0);
+        touches.down(1, 2, 0.0, 0.0);
+        assert_eq!(touches.cancel(), (vec![1, 2], Some(1)));
+        assert!(touches.frame().is_empty());
+        assert!(touches.motion(0, |_| (1.0, 1.0)).is_none());
+        assert!(touches.up(1).is_none());
+        assert_eq!(touches.cancel(), (vec![], None));
+
+        // The next finger down is the primary one again.
+        let input = touches.down(2, 0, 0.0, 0.0);
+        assert!(matches!(input[..], [.., Input::Touch(true)]));
+    }
+
+    #[test]
+    fn closed_window_points_removed() {
+        let mut touches = Touches::new();
+
+        touches.down(1, 0, 0.0, 0.0);
+        touches.down(2, 1, 0.0, 0.0);
+        touches.remove_window(1);
+        assert_eq!(touches.frame(), vec![(2, vec![touch(1, Down, 0.0, 0.0)])]);
+        assert!(touches.up(0).is_none());
+        let input = touches.down(2, 2, 0.0, 0.0);
+        assert!(matches!(input[..], [.., Input::Touch(true)]));
+    }
+}